@@ -0,0 +1,76 @@
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use snake_game::snake::{Direction, Snake};
+use snake_game::{Game, GameState};
+
+const SNAKE_LENGTHS: [i32; 4] = [10, 100, 1_000, 10_000];
+
+//A snake lying along the middle row of a board just wide enough to hold it,
+//so it can run right forever (wrapping around) without biting itself.
+fn long_snake_game(length: i32, apple_loc: (i32, i32)) -> Game {
+    let game_size = (length + 2, 3);
+    Game::new_constructed(
+        game_size,
+        Snake::new(length - 1, 1, length, Direction::Right, game_size),
+        GameState::Moving(Direction::Right),
+        0.0,
+        apple_loc,
+        0,
+        0,
+    )
+}
+
+//One tick of movement and collision checking, for growing snake lengths.
+//With the occupancy grid this should stay flat as the snake gets longer.
+fn bench_step(c: &mut Criterion) {
+    let mut group = c.benchmark_group("step");
+    for length in SNAKE_LENGTHS {
+        let mut game = long_snake_game(length, (0, 0));
+        group.bench_with_input(BenchmarkId::from_parameter(length), &length, |b, _| {
+            b.iter(|| game.step())
+        });
+    }
+    group.finish();
+}
+
+//Eating an apple and spawning the next one on a board the snake nearly fills.
+fn bench_eat_and_spawn(c: &mut Criterion) {
+    let mut group = c.benchmark_group("eat_and_spawn");
+    for length in SNAKE_LENGTHS {
+        //The apple sits right in front of the head
+        let game = long_snake_game(length, (length, 1));
+        group.bench_with_input(BenchmarkId::from_parameter(length), &length, |b, _| {
+            b.iter_batched(
+                || game.clone(),
+                |mut game| {
+                    game.step(); // eat the apple
+                    game.step(); // grow and spawn the next one
+                    game
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+//A long headless run, the way a bot simulation drives the rules.
+fn bench_million_ticks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("simulation");
+    group.sample_size(10);
+    group.bench_function("1M ticks, length 1000", |b| {
+        b.iter_batched(
+            || long_snake_game(1_000, (0, 0)),
+            |mut game| {
+                for _ in 0..1_000_000 {
+                    game.step();
+                }
+                game
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_step, bench_eat_and_spawn, bench_million_ticks);
+criterion_main!(benches);
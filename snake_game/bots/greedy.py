@@ -0,0 +1,46 @@
+#!/usr/bin/env python3
+# An example bot for --bot: heads for the apple without running into anything it can see.
+# Run it with `cargo run -- --bot "python3 bots/greedy.py"`.
+import json
+import sys
+
+MOVES = {"up": (0, -1), "down": (0, 1), "left": (-1, 0), "right": (1, 0)}
+OPPOSITE = {"up": "down", "down": "up", "left": "right", "right": "left"}
+
+
+def next_cell(game, cell, move):
+    width, height = game["size"]
+    x, y = cell[0] + MOVES[move][0], cell[1] + MOVES[move][1]
+    if game["edges"] == "wrap":
+        return [x % width, y % height]
+    if 0 <= x < width and 0 <= y < height:
+        return [x, y]
+    return None
+
+
+def distance(game, a, b):
+    width, height = game["size"]
+    dx, dy = abs(a[0] - b[0]), abs(a[1] - b[1])
+    if game["edges"] == "wrap":
+        dx, dy = min(dx, width - dx), min(dy, height - dy)
+    return dx + dy
+
+
+def choose(game):
+    head = game["body"][0]
+    # The tail moves out of the way, everything else blocks
+    blocked = game["body"][:-1] + game["obstacles"]
+    safe = []
+    for move in MOVES:
+        if move == OPPOSITE[game["heading"]] and len(game["body"]) > 1:
+            continue
+        cell = next_cell(game, head, move)
+        if cell is not None and cell not in blocked:
+            safe.append((distance(game, cell, game["apple"]), move))
+    if not safe:
+        return game["heading"]
+    return min(safe)[1]
+
+
+for line in sys.stdin:
+    print(json.dumps({"move": choose(json.loads(line))}), flush=True)
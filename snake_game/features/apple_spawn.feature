@@ -3,4 +3,14 @@ Feature: Apple Spawning
 Scenario: Apple spawns when no apple is on the screen
 Given we have a snake game
 When an apple has been eaten
-Then an apple should be spawned
\ No newline at end of file
+Then an apple should be spawned
+
+Scenario: The same seed always places the apple in the same spot
+Given we have a snake game with seed 42
+Then another game with seed 42 places the apple in the same spot
+
+Scenario: Apple positions can be predicted from the seed
+Given we have a snake game with seed 7
+Then the apple starts at 2,3
+And another game with seed 7 places the same 10 apples in a row
+And a game with seed 8 places its first 10 apples differently
@@ -0,0 +1,66 @@
+Feature: Autopilot
+
+Scenario: The autopilot heads straight for the apple
+Given we have a snake game with the apple at 15,10
+Then the autopilot steers right
+
+Scenario: The autopilot turns towards the apple
+Given we have a snake game with the apple at 10,2
+Then the autopilot steers up
+
+Scenario: The autopilot fills a small board
+Given we have a 10 by 10 snake game with solid edges and seed 3
+When the autopilot plays for 5000 ticks
+Then the game is won
+
+Scenario Outline: The autopilot keeps scoring without dying
+Given we have a 20 by 20 snake game with <edges> edges and seed <seed>
+When the autopilot plays for 2000 ticks
+Then it does not die
+And it has at least 60 points
+
+Examples:
+| edges | seed |
+| solid | 1    |
+| wrap  | 2    |
+
+Scenario: The autopilot steers around obstacles
+Given we have the level "levels/box.txt"
+When the autopilot plays for 1500 ticks
+Then it does not die
+And it has at least 40 points
+
+Scenario Outline: The cycle autopilot fills the board
+Given we have a <width> by <height> snake game with <edges> edges and seed <seed>
+Then the cycle visits every cell once
+When the cycle autopilot plays for 200000 ticks
+Then the game is won
+
+Examples:
+| width | height | edges | seed |
+| 4     | 4      | solid | 1    |
+| 6     | 5      | solid | 2    |
+| 5     | 6      | solid | 3    |
+| 7     | 4      | wrap  | 4    |
+| 5     | 5      | wrap  | 5    |
+| 9     | 7      | wrap  | 6    |
+| 20    | 20     | solid | 7    |
+| 21    | 21     | wrap  | 8    |
+
+Scenario: Odd boards with solid edges have no cycle
+Given we have a 5 by 5 snake game with solid edges and seed 1
+Then there is no cycle for the autopilot
+
+Scenario: The cycle autopilot needs a board without obstacles
+Given we have the level "levels/box.txt"
+Then there is no cycle for the autopilot
+
+Scenario Outline: The autopilot starts short snakes the way the frontends drive it
+Then for every seed from 0 to 199 the <mode> autopilot starts a 20 by 20 game with a snake of length <length>
+
+Examples:
+| mode  | length |
+| path  | 1      |
+| path  | 2      |
+| cycle | 1      |
+| cycle | 2      |
@@ -0,0 +1,41 @@
+Feature: Key bindings
+
+Scenario: The default bindings match the classic controls
+Given the default settings
+Then the W key means move up while playing
+And the Left key means move left while playing
+And the Space key means pause while playing
+And the Space key means restart when the game is over
+And the Escape key means quit while playing
+And the Escape key means quit when the game is over
+And the W key means nothing when the game is over
+
+Scenario: Actions can be rebound in the settings file
+When we load the settings "keys = { pause = ['Q'], up = ['I', 'Up'] }"
+Then the Q key means pause while playing
+And the I key means move up while playing
+And the W key means nothing while playing
+And the Space key means nothing while playing
+And the Down key means move down while playing
+
+Scenario: Restart and pause may share a key
+When we load the settings "keys = { pause = ['Return'] }"
+Then the Return key means pause while playing
+And the Return key means restart when the game is over
+
+Scenario: An empty settings file keeps the defaults
+When we load the settings ""
+Then the settings are the defaults
+
+Scenario Outline: Invalid key bindings are rejected
+When we load the settings "<settings>"
+Then the settings are rejected with "<error>"
+
+Examples:
+| settings                   | error                                   |
+| keys = { pause = ['W'] }   | key W is bound to both pause and up     |
+| keys = { quit = ['R'] }    | key R is bound to both quit and restart |
+| keys = { left = ['Right'] }| key Right is bound to both left and right |
+| keys = { jump = ['J'] }    | unknown field `jump`                    |
+| keys = { up = ['Banana'] } | unknown variant `Banana`                |
+| keys = ['W']               | invalid type                            |
@@ -0,0 +1,45 @@
+Feature: Command-line options
+
+Scenario: Defaults give the classic game
+When the game is started with ""
+Then the board is 20 by 20
+And the snake is 3 long
+And the tick speed is 5 ticks per second
+And the edges are wrap
+
+Scenario: Board, snake and speed can be chosen
+When the game is started with "--width 30 --height 12 --length 6 --speed 8 --edges solid --seed 7"
+Then the board is 30 by 12
+And the snake is 6 long
+And the tick speed is 8 ticks per second
+And the edges are solid
+And the game uses seed 7
+
+Scenario: Restarting keeps the starting length
+When the game is started with "--width 30 --height 12 --length 6"
+And the game is restarted
+Then the snake is 6 long
+
+Scenario: A level file sets the board
+When the game is started with "--level levels/box.txt --edges solid"
+Then the board is 20 by 15
+And the level has 82 obstacles
+
+Scenario Outline: Invalid options are rejected
+When the game is started with "<args>"
+Then starting fails with "<error>"
+
+Examples:
+| args                          | error                                                            |
+| --width 8 --length 6          | a snake of length 6 does not fit on this board, the maximum is 5 |
+| --width 1                     | a 1x20 board is too small                                        |
+| --width 50000 --height 50000  | a 50000x50000 board is too large, it can be at most 1000x1000    |
+| --length 0                    | the snake length must be at least 1                              |
+| --speed 0                     | the speed must be a positive number of ticks per second          |
+| --scale 10                    | the scale must be between 0.25 and 4                             |
+| --edges bouncy                | unknown edge mode 'bouncy', expected wrap or solid               |
+| --level levels/box.txt --width 5 | cannot be used with                                           |
+| --level levels/missing.txt    | levels/missing.txt: could not read level                         |
+| --gif run.gif                 | required arguments were not provided                             |
+| --autopilot greedy            | unknown autopilot 'greedy', expected path or cycle               |
+| --width 5 --height 5 --edges solid --autopilot cycle | the cycle autopilot cannot fill this 5x5 board |
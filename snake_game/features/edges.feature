@@ -0,0 +1,33 @@
+Feature: Board edges
+
+Scenario Outline: Wrapping around every edge of a non-square board
+Given we have a 30 by 10 snake game with wrap edges
+When the snake runs <dir> from <x>,<y>
+Then it does not die
+And the snake head is at <new_x>,<new_y>
+
+Examples:
+| dir   | x  | y | new_x | new_y |
+| left  | 0  | 5 | 29    | 5     |
+| right | 29 | 5 | 0     | 5     |
+| up    | 15 | 0 | 15    | 9     |
+| down  | 15 | 9 | 15    | 0     |
+
+Scenario Outline: Solid walls kill the snake on every edge of a non-square board
+Given we have a 10 by 30 snake game with solid edges
+When the snake runs <dir> from <x>,<y>
+Then it dies from hitting a wall
+And the snake head is at <x>,<y>
+
+Examples:
+| dir   | x | y  |
+| left  | 0 | 15 |
+| right | 9 | 15 |
+| up    | 5 | 0  |
+| down  | 5 | 29 |
+
+Scenario: Moving along a solid wall is safe
+Given we have a 10 by 30 snake game with solid edges
+When the snake runs down from 0,0
+Then it does not die
+And the snake head is at 0,1
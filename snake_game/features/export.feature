@@ -0,0 +1,24 @@
+Feature: Exporting replays
+
+Scenario: A replay is rendered into an animated GIF
+Given we have a 10 by 10 snake game with solid edges and seed 21
+When the game is recorded while chasing the apple until it ends
+And the replay is exported as a GIF
+Then the GIF has a frame for every tick and one for the end
+And every GIF frame is 300 by 350 pixels
+And GIF frame 1 lasts 1 hundredths of a second
+And GIF frame 2 lasts 20 hundredths of a second
+And the last GIF frame lasts 200 hundredths of a second
+
+Scenario: A won game is exported with its final board
+Given we have a 4 by 4 snake game with solid edges and seed 1
+When the game is recorded while the cycle autopilot plays until it ends
+Then the game is won
+When the replay is exported as a GIF
+Then the GIF has a frame for every tick and one for the end
+And the last GIF frame lasts 200 hundredths of a second
+
+Scenario: A board too large for a GIF is rejected before drawing
+Given we have a 3000 by 10 snake game with solid edges and seed 1
+When the game is exported as a GIF before it starts
+Then the export fails with "animation would be 75050x"
@@ -0,0 +1,74 @@
+Feature: External bots
+
+Scenario: A bot in another program steers the snake
+Given we have a snake game
+When the bot 'sh tests/bots/reply.sh {"move":"up"}' plays for 3 ticks
+Then the snake head is at 10,7
+And it does not die
+
+Scenario: The bot gets the game as JSON and every line is logged
+Given we have a snake game
+When the bot 'sh tests/bots/reply.sh {"move":"up"}' plays for 2 ticks
+Then the bot log contains '> {"tick":0,"state":"waiting","size":[20,20],"edges":"wrap","heading":"right","body":[[10,10],[9,10],[8,10]],"apple":[19,19],"points":0,"obstacles":[]}'
+And the bot log contains '> {"tick":1,"state":"moving","size":[20,20],"edges":"wrap","heading":"up","body":[[10,9],[10,10],[9,10]]'
+And the bot log has 2 lines starting with '< {"move":"up"}'
+
+Scenario: Invalid answers keep the snake going straight
+Given we have a snake game
+When the bot "sh tests/bots/reply.sh jump" plays for 3 ticks
+Then the snake head is at 13,10
+And the bot log has 3 lines starting with "! illegal move"
+
+Scenario: Unknown moves keep the snake going straight
+Given we have a snake game
+When the bot 'sh tests/bots/reply.sh {"move":"jump"}' plays for 2 ticks
+Then the snake head is at 12,10
+And the bot log contains "! illegal move: unknown direction 'jump'"
+
+Scenario: Turning straight back is illegal
+Given we have a snake game
+When the bot 'sh tests/bots/reply.sh {"move":"left"}' plays for 3 ticks
+Then the snake head is at 13,10
+And the bot log contains "! illegal move: left turns straight back"
+
+Scenario: A bot that does not answer in time is skipped
+Given we have a snake game
+When the bot "sleep 60" plays for 3 ticks with a timeout of 20 ms
+Then the snake head is at 13,10
+And the bot log has 3 lines starting with "! no move within 20 ms"
+
+Scenario: A bot that never reads its input cannot block the game
+Given we have a snake game
+When the bot "sleep 60" plays for 2000 ticks with a timeout of 1 ms
+Then the snake head is at 10,10
+And it does not die
+And the bot log contains "! the bot is not reading its input"
+
+Scenario: Late answers are not used for the next move
+Given we have a snake game
+When the bot "sh tests/bots/late_start.sh" plays for 3 ticks with a timeout of 1000 ms
+Then the snake head is at 11,8
+And the bot log has 1 lines starting with "! no move within 1000 ms"
+
+Scenario: The snake goes straight on once the bot has exited
+Given we have a snake game
+When the bot "true" plays for 3 ticks
+Then the snake head is at 13,10
+And it does not die
+
+Scenario: External bots can enter tournaments
+Given a tournament of 2 games on a 10 by 10 board with solid edges
+When the external bot 'sh tests/bots/reply.sh {"move":"up"}' plays the tournament
+Then 2 games ended with "hit the wall"
+And the report says 'sh tests/bots/reply.sh {"move":"up"}: 2 games'
+
+Scenario Outline: Bot options are checked
+When the game is started with "<args>"
+Then starting fails with "<error>"
+
+Examples:
+| args                            | error                                          |
+| --bot no-such-bot-program       | no-such-bot-program: could not start no-such-bot-program |
+| --bot-timeout 50                | required arguments were not provided           |
+| --bot true --autopilot          | cannot be used with                            |
+| --bot true --bot-timeout 0      | 0 is not in 1..                                |
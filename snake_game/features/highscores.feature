@@ -0,0 +1,36 @@
+Feature: High scores
+
+Scenario: A run makes the table while there is room
+Given we have a high-score table with the scores "50 40 30"
+When a run scoring 35 is recorded
+Then the run is ranked 3
+And the table lists the scores "50 40 35 30"
+
+Scenario: The table only keeps the best ten runs
+Given we have a high-score table with the scores "100 90 80 70 60 50 40 30 20 10"
+When a run scoring 55 is recorded
+Then the run is ranked 6
+And the table lists the scores "100 90 80 70 60 55 50 40 30 20"
+
+Scenario: A run below a full table does not qualify
+Given we have a high-score table with the scores "100 90 80 70 60 50 40 30 20 10"
+Then a score of 10 does not qualify
+And a score of 11 qualifies
+
+Scenario: A run without points never qualifies
+Given we have a high-score table with the scores ""
+Then a score of 0 does not qualify
+
+Scenario: Ties keep the older run first
+Given we have a high-score table with the scores "50 40"
+When a run scoring 40 is recorded
+Then the run is ranked 3
+
+Scenario: The table survives saving and loading
+Given we have a high-score table with the scores "50 40 30"
+Then the table is the same after saving and loading it
+
+Scenario: A finished game is recorded with its details
+Given we have a 30 by 10 snake game with solid edges
+When the game is recorded for "abc"
+Then the recorded run is "abc" on a 30x10 "solid" board with length 3
@@ -0,0 +1,46 @@
+Feature: Levels and obstacles
+
+Scenario: Loading a level file
+Given we have the level "levels/box.txt"
+Then the board is 20 by 15
+And the level has 82 obstacles
+And the snake starts at 8,7 heading right
+
+Scenario: Snake dies when hitting an obstacle
+Given we have the level "...../..>#./....."
+When the level is played for 1 ticks
+Then it dies from hitting an obstacle
+
+Scenario: Apples never spawn on obstacles
+Given we have the level "levels/cross.txt"
+Then for every seed from 0 to 50 the first apple spawns on a free cell
+
+Scenario: Restarting a level keeps its obstacles
+Given we have the level "...#./..>#./...#."
+When the level is played for 1 ticks
+And the restart button is pressed
+Then the level has 3 obstacles
+And the snake starts at 2,1 heading right
+
+Scenario Outline: Invalid levels are rejected
+When we parse the level "<level>"
+Then the level is rejected with "<error>"
+
+Examples:
+| level     | error                                   |
+|           | level is empty                          |
+| ..../...  | row 2 is 3 tiles wide, expected 4       |
+| .>../..x. | unknown tile 'x' at row 2, column 3     |
+| ..../.... | level has no snake start (^, v, < or >) |
+| .>../..<. | second snake start at row 2, column 3   |
+| .>..      | the starting snake                      |
+| #.>.      | the starting snake                      |
+
+Scenario Outline: Levels larger than the biggest board are rejected
+When we parse a level of <width> by <height> tiles
+Then the level is rejected with "<error>"
+
+Examples:
+| width | height | error                                              |
+| 1001  | 3      | level is 1001x3 tiles, it can be at most 1000x1000 |
+| 3     | 1001   | level is 3x1001 tiles, it can be at most 1000x1000 |
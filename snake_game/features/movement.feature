@@ -28,4 +28,19 @@ Then the snake moves up
 Scenario: Wrapping movement
 Given we have a snake game
 When the snake moves over the edge
-Then the snake appears on the opposite side
\ No newline at end of file
+Then the snake appears on the opposite side
+
+Scenario: Two quick turns within one tick are both applied
+Given we have a snake game
+When the snake is moving right and we quickly tap "up left"
+Then over the next ticks the snake moves "up left"
+
+Scenario: A quick turn is checked against the queued direction
+Given we have a snake game
+When the snake is moving right and we quickly tap "up down"
+Then over the next ticks the snake moves "up up"
+
+Scenario: Turns beyond the input buffer are dropped
+Given we have a snake game
+When the snake is moving right and we quickly tap "up left down right"
+Then over the next ticks the snake moves "up left down down"
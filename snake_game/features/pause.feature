@@ -0,0 +1,28 @@
+Feature: Pausing
+
+Scenario: Pausing freezes the snake and the clock
+Given we have a snake game
+When the snake is moving right and the P button is pressed
+And 5 frames of 1 seconds pass while paused
+Then the game is paused while moving right
+And the snake head has moved 0 cells to the right
+And the clock has not moved
+
+Scenario: Resuming continues in the remembered direction
+Given we have a snake game
+When the snake is moving right and the P button is pressed
+And the P button is pressed again
+Then the snake is moving right
+And the snake head has moved 1 cells to the right after a tick
+
+Scenario: Turns are ignored while paused
+Given we have a snake game
+When the snake is moving right and the P button is pressed
+And we tap up while paused
+And the P button is pressed again
+Then the snake head has moved 1 cells to the right after a tick
+
+Scenario: The game cannot be paused before it starts
+Given we have a snake game
+When the P button is pressed while waiting
+Then the game is still waiting
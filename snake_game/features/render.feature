@@ -0,0 +1,34 @@
+Feature: Rendering
+
+Scenario: The board is drawn below the HUD
+Given we have the level ".....#../..>...../........"
+When the game is drawn
+Then 3 snake cells are drawn
+And 1 apple cells are drawn
+And 1 obstacle cells are drawn
+And a snake cell is drawn at 2,3
+And a obstacle cell is drawn at 5,2
+And 16 fill cells are drawn
+And the text "Points: 0   Length: 3" is drawn
+
+Scenario: Solid edges are drawn as walls around the board
+Given we have a 4 by 2 snake game with solid edges
+When the game is drawn
+Then 16 wall cells are drawn
+And a wall cell is drawn at 0,2
+
+Scenario: A paused game is dimmed and shows how to resume
+Given we have the level "......../..>...../........"
+When the level is played for 1 ticks
+And the game is paused
+And the game is drawn
+Then 40 fill cells are drawn
+And the text "Paused" is drawn
+And the text "Press P to resume" is drawn
+
+Scenario: A finished game shows the score
+Given we have the level "......../..>#..../........"
+When the level is played for 1 ticks
+And the game is drawn
+Then the text "Game over" is drawn
+And the text "Score: 0" is drawn
@@ -0,0 +1,60 @@
+Feature: Replays
+
+Scenario: A recorded game plays back to the same score
+Given we have a 10 by 10 snake game with solid edges and seed 4
+When the game is recorded while turning "up right" every 3 ticks until it ends
+Then the replay plays back to the recorded points and ticks
+
+Scenario: Eating apples plays back the same
+Given we have a 10 by 10 snake game with solid edges and seed 21
+When the game is recorded while chasing the apple until it ends
+Then the recorded game has points
+And the replay plays back to the recorded points and ticks
+
+Scenario: Replays survive being saved and loaded
+Given we have a 12 by 8 snake game with solid edges and seed 9
+When the game is recorded while turning "down left" every 4 ticks until it ends
+And the replay is saved to a file and loaded
+Then the loaded replay matches the recorded one
+And the replay plays back to the recorded points and ticks
+
+Scenario: Inputs are stored compactly
+Given we have a 10 by 10 snake game with solid edges and seed 1
+When the game is recorded while turning "up left" every 2 ticks until it ends
+Then the replay inputs start with "0U 2L 4U"
+
+Scenario: Direction keys pressed while paused are not recorded
+Given we have a 10 by 10 snake game with solid edges and seed 1
+When the snake starts moving up while recording
+And the game is paused and the left button is pressed while recording
+Then the replay has 1 inputs
+
+Scenario: A replay that no longer matches is reported
+Given we have a 10 by 10 snake game with solid edges and seed 4
+When the game is recorded while turning "up right" every 3 ticks until it ends
+And the recorded points are changed to 99
+Then the replay fails to verify with "expected 99 points"
+
+Scenario Outline: Invalid replays are rejected
+When we load the replay '<replay>'
+Then the replay is rejected with "<error>"
+
+Examples:
+| replay                    | error                                            |
+| {"version": 2}            | replay has version 2, this game reads version 1  |
+| {"version": 1}            | missing field                                    |
+| snake                     | invalid replay                                   |
+
+Scenario Outline: Replays that cannot be played are rejected
+Given we have a 10 by 10 snake game with solid edges and seed 4
+When the game is recorded while turning "up right" every 3 ticks until it ends
+And the replay has its <field> set to '<value>' and is loaded
+Then the replay is rejected with "<error>"
+
+Examples:
+| field       | value                                                                      | error                                      |
+| length      | 0                                                                          | the snake length must be at least 1, got 0 |
+| size        | [50000,50000]                                                              | a 50000x50000 board is too large           |
+| speed_curve | {"Fixed":0.0}                                                              | the speed curve is broken                  |
+| level       | {"name":"x","size":[5,5],"obstacles":[],"start":[2,2],"heading":"Right"}   | the level does not match the board size    |
+| level       | {"name":"x","size":[10,10],"obstacles":[],"start":[0,0],"heading":"Right"} | the snake starts outside the level         |
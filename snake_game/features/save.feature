@@ -0,0 +1,61 @@
+Feature: Saving and loading
+
+Scenario: A loaded game continues exactly like the saved one
+Given we have a snake game with seed 11
+When the game is played for 40 ticks with turns "up left down right"
+And the game is saved and loaded
+Then the loaded game matches the saved one
+And both games stay identical for 200 more ticks
+
+Scenario: The tick accumulator and queued turns are kept
+Given we have a snake game with seed 3
+When the snake starts moving and turns "up left" are queued mid-tick
+And the game is saved and loaded
+Then the loaded game has 2 queued turns
+And the loaded clock interval matches the saved one
+
+Scenario: Saving to a file and loading it back
+Given we have a snake game with seed 5
+When the game is played for 10 ticks with turns "down"
+And the game is saved to a file and loaded
+Then the loaded game matches the saved one
+
+Scenario Outline: Invalid save files are rejected
+When we load the save '<save>'
+Then the save is rejected with "<error>"
+
+Examples:
+| save                        | error                                                  |
+| {"version": 99, "game": {}} | save file has version 99, this game reads version 2    |
+| {"version": 2, "game": {}}  | missing field                                          |
+| snake                       | invalid save file                                      |
+
+Scenario: The occupancy grid is rebuilt from the snake on loading
+Given we have a snake game with seed 7
+When the game is saved with its board set to '{"occupants": []}' and loaded
+Then the loaded game matches the saved one
+And both games stay identical for 200 more ticks
+
+Scenario: Deserializing a game on its own rebuilds the occupancy grid too
+Given we have a snake game with seed 7
+When the game is deserialized on its own
+Then the loaded game matches the saved one
+And both games stay identical for 200 more ticks
+
+Scenario Outline: Saves that cannot be played are rejected
+Given we have a snake game with seed 7
+When the game is saved with its <field> set to '<value>' and loaded
+Then the save is rejected with "<error>"
+
+Examples:
+| field        | value                                                            | error                                                              |
+| game_size    | [0,20]                                                           | the board is empty                                                 |
+| game_size    | [50000,50000]                                                    | the board is too large                                             |
+| game_size    | [10,10]                                                          | the board sizes do not match                                       |
+| snake        | {"body":[],"game_size":[20,20],"prev_dir":"Right"}               | the snake has no body                                              |
+| snake        | {"body":[{"x":25,"y":3}],"game_size":[20,20],"prev_dir":"Right"} | the snake is outside the board                                     |
+| apple_loc    | [3,-1]                                                           | the apple is outside the board                                     |
+| start_length | 0                                                                | the snake length must be at least 1, got 0                         |
+| start_length | 15                                                               | a snake of length 15 does not fit on this board, the maximum is 11 |
+| clock        | {"step_time":0.0,"interval":0.0}                                 | the clock is broken                                                |
+| speed_curve  | {"Fixed":-1.0}                                                   | the speed curve is broken                                          |
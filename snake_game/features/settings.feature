@@ -0,0 +1,34 @@
+Feature: Settings file
+
+Scenario: The example settings file shows the defaults
+When we load the settings file "settings.example.toml"
+Then the settings are the defaults
+
+Scenario: Settings are merged over the defaults
+When we load the settings "colours = { apple = [1.0, 0.0, 0.0, 1.0] }"
+Then the apple colour is 1.0,0.0,0.0,1.0
+And the snake colour is the default
+And the key bindings are the defaults
+
+Scenario: The step time comes from the settings
+When we load the settings "speed = { step_time = 0.1 }"
+And the game is started with ""
+Then the tick speed is 10 ticks per second
+
+Scenario: The command line overrides the step time
+When we load the settings "speed = { step_time = 0.1 }"
+And the game is started with "--speed 4"
+Then the tick speed is 4 ticks per second
+
+Scenario Outline: Invalid settings are rejected
+When we load the settings "<settings>"
+Then the settings are rejected with "<error>"
+
+Examples:
+| settings                                           | error                                                  |
+| colours = { snake = [0.1, 1.5, 0.1, 1.0] }         | colours.snake is 1.5, expected a value from 0 to 1     |
+| colours = { snake = [0.1, 0.1, 1.0] }              | invalid length 3, expected an array of length 4        |
+| window = { block_size = 2.0 }                      | window.block_size is 2, expected a value from 5 to 100 |
+| speed = { step_time = 0.0 }                        | speed.step_time is 0                                   |
+| speed = { step_time = 0.1, min_step_time = 0.2 }   | speed.min_step_time is 0.2, expected a value from 0.01 to 0.1 |
+| window = { fullscreen = true }                     | unknown field `fullscreen`                             |
@@ -8,4 +8,26 @@ Then it does not die
 Scenario: Snake dies when hitting itself
 Given we have a snake game
 When the snake moves to a spot that is already occupied by the snake
-Then it dies
\ No newline at end of file
+Then it dies
+
+Scenario: Board stays frozen after the snake dies
+Given we have a snake game
+When the snake moves to a spot that is already occupied by the snake
+Then the board stays frozen
+
+Scenario: Movement keys do nothing after the snake dies
+Given we have a snake game
+When the snake moves to a spot that is already occupied by the snake
+And the D button is pressed after death
+Then it dies
+
+Scenario: Restarting after the snake dies
+Given we have a snake game
+When the snake moves to a spot that is already occupied by the snake
+And the restart button is pressed
+Then a new game starts on the same board
+
+Scenario: Snake does not die when moving into the cell its tail is leaving
+Given we have a snake game
+When the snake chases its own tail
+Then it does not die
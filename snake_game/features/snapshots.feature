@@ -0,0 +1,32 @@
+Feature: Snapshots
+
+Scenario: The board and HUD match the golden image
+Given we have the level "levels/box.txt"
+Then the snapshot is 500 by 425 pixels
+And the snapshot matches "board.png"
+
+Scenario: Solid edges draw walls around the board
+Given we have a 6 by 4 snake game with solid edges
+Then the snapshot matches "walls.png"
+
+Scenario: The game-over screen matches the golden image
+Given we have the level "............/....>#....../............/............/............/............/............/............/............"
+When the level is played for 1 ticks
+Then the snapshot matches "game_over.png"
+
+Scenario: The pause screen matches the golden image
+Given we have the level "............/....>......./............/............/............/............/............/............/............"
+When the level is played for 1 ticks
+And the game is paused
+Then the snapshot matches "paused.png"
+
+Scenario: Blocks are drawn at the block size in the settings colours
+Given we have the level ".....#../..>...../........"
+Then the snapshot pixel at 0,0 is 43,61,79
+And the snapshot pixel at 60,85 is 46,204,112
+And the snapshot pixel at 130,55 is 112,84,64
+And the snapshot pixel at 190,100 is 52,73,94
+
+Scenario: Snapshots can be stored as PNG
+Given we have the level "levels/box.txt"
+Then the snapshot is the same after a PNG round trip
@@ -0,0 +1,36 @@
+Feature: Speed curves
+
+Scenario Outline: The step time shrinks with the points scored
+Then with <points> points the <curve> speed curve ticks <speed> times per second
+
+Examples:
+| curve   | points | speed |
+| fixed   | 0      | 5     |
+| fixed   | 50     | 5     |
+| linear  | 0      | 5     |
+| linear  | 5      | 6.666666666666667 |
+| linear  | 50     | 10    |
+| stepped | 4      | 5     |
+| stepped | 5      | 6.666666666666667 |
+| stepped | 100    | 10    |
+| custom  | 2      | 5     |
+| custom  | 3      | 6.666666666666667 |
+| custom  | 9      | 6.666666666666667 |
+| custom  | 10     | 8     |
+
+Scenario: Eating an apple speeds the game up
+Given we have a snake game with a linear speed curve
+When the snake eats an apple reaching 5 points
+Then the tick speed is 6.666666666666667 ticks per second
+
+Scenario: The speed is capped by the curve minimum
+Given we have a snake game with a linear speed curve
+When the snake eats an apple reaching 99 points
+Then the tick speed is 10 ticks per second
+
+Scenario: Restarting keeps the speed curve but resets the speed
+Given we have a snake game with a linear speed curve
+When the snake eats an apple reaching 5 points
+And the game is restarted
+Then the tick speed is 5 ticks per second
+And the game uses a linear speed curve
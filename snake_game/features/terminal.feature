@@ -0,0 +1,39 @@
+Feature: Terminal frontend
+
+Scenario: Terminal keys use the same bindings as the window
+Given the default settings
+Then the terminal key w means move up while playing
+And the terminal key W means move up while playing
+And the terminal key Left means move left while playing
+And the terminal key Space means pause while playing
+And the terminal key Enter means restart when the game is over
+And the terminal key Esc means quit while playing
+And the terminal key Home is not a game key
+
+Scenario: Rebound keys work in the terminal
+When we load the settings "keys = { pause = ['Q'], save = ['F2'] }"
+Then the terminal key q means pause while playing
+And the terminal key p means nothing while playing
+
+Scenario: The board is drawn with two columns per cell
+Given we have the level "......../..>#..../........"
+Then terminal line 1 reads " Points: 0   Length: 3"
+And terminal line 2 reads " Time: 0:00   Speed: 5.0/s"
+And terminal line 4 reads "██████▒▒    ()  "
+
+Scenario: Solid edges are drawn as walls
+Given we have a 4 by 2 snake game with solid edges
+Then terminal line 3 reads "▓▓▓▓▓▓▓▓▓▓▓▓"
+
+Scenario: A paused game shows how to resume
+Given we have the level "......../..>...../......../........"
+When the level is played for 1 ticks
+And the game is paused
+Then terminal line 5 reads "  Paused()      "
+And terminal line 7 reads "  Press P to resume"
+
+Scenario: A finished game shows how to restart
+Given we have the level "......../..>#..../......../........"
+When the level is played for 1 ticks
+Then terminal line 5 reads "  Game over     "
+And terminal line 10 reads "  Press R to restart"
@@ -0,0 +1,29 @@
+Feature: Fixed-timestep simulation
+
+Scenario: Each step advances the snake exactly one cell
+Given we have a snake game
+When the snake is stepped 3 times while moving right
+Then the snake head has moved 3 cells to the right
+And 3 ticks have passed
+
+Scenario: A frame shorter than a tick does not move the snake
+Given we have a snake game
+When a frame shorter than a tick passes while moving right
+Then the snake head has moved 0 cells to the right
+And 0 ticks have passed
+
+Scenario: A very long frame advances at most one tick
+Given we have a snake game
+When a frame of 10 seconds passes while moving right
+Then the snake head has moved 1 cells to the right
+And 1 ticks have passed
+
+Scenario: Elapsed time only counts while the snake is moving
+Given we have a snake game
+When 3 frames of 0.5 seconds pass while waiting
+And 3 frames of 0.5 seconds pass while moving right
+Then 1.5 seconds have elapsed
+
+Scenario: The tick speed follows the step time
+Given we have a snake game
+Then the tick speed is 5 ticks per second
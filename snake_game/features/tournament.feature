@@ -0,0 +1,53 @@
+Feature: Tournament
+
+Scenario: The cycle bot wins every game
+Given a tournament of 4 games on a 10 by 10 board with solid edges
+When the cycle bot plays the tournament
+Then the report covers 4 games
+And 4 games ended with "won"
+And the median score is 97.0
+And the report says "cycle: 4 games"
+
+Scenario: The path bot scores well
+Given a tournament of 4 games on a 10 by 10 board with wrap edges
+When the path bot plays the tournament
+Then the mean score is at least 60
+And 0 games ended with "hit the wall"
+
+Scenario: Any agent can take part
+Given a tournament of 3 games on a 10 by 10 board with solid edges
+When a bot that always goes right plays the tournament
+Then 3 games ended with "hit the wall"
+And every game lasted 5 ticks
+And the report says "stubborn: 3 games"
+And the report says "endings: 3 hit the wall"
+
+Scenario: Turning straight back at the start is ignored
+Given a tournament of 2 games on a 10 by 10 board with solid edges
+When a bot that always goes left plays the tournament
+Then 2 games ended with "hit the wall"
+And every game lasted 5 ticks
+
+Scenario: Games that go on too long are stopped
+Given a tournament of 2 games on a 10 by 10 board with wrap edges
+When a bot that always goes up plays the tournament
+Then 2 games ended with "out of time"
+And every game lasted 20000 ticks
+
+Scenario: Every bot gets the same games
+Given a tournament of 3 games on a 10 by 10 board with wrap edges
+When the path bot plays the tournament
+And the path bot plays the tournament
+Then the last two reports are the same
+
+Scenario: The cycle bot needs a board with a cycle
+Given a tournament of 2 games on a 5 by 5 board with solid edges
+Then the cycle bot cannot play the tournament
+
+Scenario: Tournament options are checked like the game's
+When the tournament is started with "--width 1 --games 3"
+Then starting fails with "a 1x20 board is too small"
+
+Scenario: A tournament needs at least one game
+When the tournament is started with "--games 0"
+Then starting fails with "0 is not in 1.."
@@ -0,0 +1,11 @@
+Feature: Winning the game
+
+Scenario: Eating the last apple on a full board wins the game
+Given we have a 4 by 1 snake game with one free cell
+When the snake moves to a spot with an apple
+Then the game is won
+And the board stays frozen
+
+Scenario: Apples only spawn on cells the snake does not cover
+Given we have a 5 by 2 snake game with a long snake
+Then for every seed from 0 to 50 the next apple spawns on a free cell
@@ -0,0 +1,79 @@
+use std::collections::VecDeque;
+
+use crate::snake::{Block, Direction, EdgePolicy, Snake};
+use crate::{Game, GameState};
+
+//What a bot gets to see of a game. Everything needed to steer, but not the apple generator,
+//so a bot cannot look ahead at where the next apple will appear.
+#[derive(Debug, Clone, Copy)]
+pub struct GameView<'a> {
+    game: &'a Game,
+}
+
+//A bot that steers the snake, e.g. the autopilot. It is asked for a direction once per tick.
+pub trait Agent {
+    //Short name for reports, e.g. "path"
+    fn name(&self) -> &str;
+    fn choose(&mut self, view: &GameView) -> Direction;
+    //Called before a new game, e.g. after a restart or a load
+    fn reset(&mut self, _view: &GameView) {}
+}
+
+impl<'a> GameView<'a> {
+    pub fn new(game: &'a Game) -> Self {
+        GameView { game }
+    }
+
+    //Where one move in `dir` from `pos` leads, None into a solid wall
+    pub fn next_position(&self, pos: (i32, i32), dir: &Direction) -> Option<(i32, i32)> {
+        let (dx, dy) = dir.delta();
+        let block = Snake::calculate_next_position(
+            pos,
+            dx,
+            dy,
+            self.get_game_size(),
+            self.get_edge_policy(),
+        )?;
+        Some((block.x, block.y))
+    }
+
+    //Getters
+    //The snake from head to tail. Right after eating the tail is in there twice.
+    pub fn get_body(&self) -> &'a VecDeque<Block> {
+        self.game.snake.get_body()
+    }
+    pub fn get_head_pos(&self) -> (i32, i32) {
+        self.game.snake.get_head_pos()
+    }
+    pub fn get_tail_pos(&self) -> (i32, i32) {
+        self.game.snake.get_tail_pos()
+    }
+    //The direction of the last move, turning straight back is ignored
+    pub fn get_heading(&self) -> Direction {
+        self.game.snake.prev_dir.clone()
+    }
+    pub fn get_apple_loc(&self) -> (i32, i32) {
+        self.game.get_apple_loc()
+    }
+    pub fn get_game_size(&self) -> (i32, i32) {
+        self.game.get_game_size()
+    }
+    pub fn get_edge_policy(&self) -> EdgePolicy {
+        self.game.get_edge_policy()
+    }
+    pub fn get_obstacles(&self) -> &'a [(i32, i32)] {
+        self.game.get_board().get_obstacles()
+    }
+    pub fn is_obstacle(&self, pos: (i32, i32)) -> bool {
+        self.game.get_board().is_obstacle(pos)
+    }
+    pub fn get_state(&self) -> GameState {
+        self.game.get_state()
+    }
+    pub fn get_points(&self) -> i32 {
+        self.game.get_points()
+    }
+    pub fn get_ticks(&self) -> u64 {
+        self.game.get_ticks()
+    }
+}
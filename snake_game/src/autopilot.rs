@@ -0,0 +1,322 @@
+use std::collections::VecDeque;
+use std::fmt;
+
+use crate::agent::{Agent, GameView};
+use crate::hamilton::CycleBot;
+use crate::snake::{Direction, EdgePolicy};
+use crate::{Game, GameState};
+
+//Lets the game play itself: asks an agent for a direction once per tick and feeds it to the
+//game, like a player pressing keys
+pub struct Autopilot {
+    //Tick of the last decision
+    decided_at: Option<u64>,
+    agent: Box<dyn Agent>,
+}
+
+//The default bot: heads for the apple along a shortest path, but only if the snake can still
+//reach its own tail afterwards; otherwise it follows its tail until the way is safe
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PathBot;
+
+//Which bot --autopilot uses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AutopilotMode {
+    //Shortest safe path to the apple
+    #[default]
+    Path,
+    //Around a Hamiltonian cycle
+    Cycle,
+}
+
+impl Autopilot {
+    pub fn new() -> Self {
+        Autopilot::with_agent(Box::new(PathBot))
+    }
+    pub fn with_agent(agent: Box<dyn Agent>) -> Self {
+        Autopilot {
+            decided_at: None,
+            agent,
+        }
+    }
+    //None if the mode is Cycle and the board has no cycle
+    pub fn for_mode(mode: AutopilotMode, game: &Game) -> Option<Self> {
+        agent_for(mode, &GameView::new(game)).map(Autopilot::with_agent)
+    }
+
+    //The direction for the coming tick, or None if there is nothing to decide right now
+    pub fn next_move(&mut self, game: &Game) -> Option<Direction> {
+        let deciding = match game.get_state() {
+            GameState::Waiting => true,
+            GameState::Moving(_) => self.decided_at != Some(game.get_ticks()),
+            _ => false,
+        };
+        if !deciding {
+            return None;
+        }
+        self.decided_at = Some(game.get_ticks());
+        let dir = self.agent.choose(&GameView::new(game));
+        //The game ignores a move straight back, and a waiting game would never start, so go
+        //straight ahead instead
+        let heading = game.get_snake().prev_dir;
+        if dir == heading.opposite() {
+            return Some(heading);
+        }
+        Some(dir)
+    }
+
+    //Call when the game is replaced by another one, e.g. a loaded one
+    pub fn reset(&mut self, game: &Game) {
+        self.decided_at = None;
+        self.agent.reset(&GameView::new(game));
+    }
+
+    //Plays without a frontend until the game is over or `max_ticks` have passed
+    pub fn play(&mut self, game: &mut Game, max_ticks: u64) {
+        while !game.is_over() && game.get_ticks() < max_ticks {
+            if let Some(dir) = self.next_move(game) {
+                game.update_move_dir(dir);
+            }
+            game.step();
+        }
+    }
+
+    //Getters
+    pub fn get_name(&self) -> &str {
+        self.agent.name()
+    }
+}
+
+impl Default for Autopilot {
+    fn default() -> Self {
+        Autopilot::new()
+    }
+}
+
+impl Agent for PathBot {
+    fn name(&self) -> &str {
+        "path"
+    }
+    fn choose(&mut self, view: &GameView) -> Direction {
+        choose_direction(view)
+    }
+}
+
+//The bot for `mode`, None if the mode is Cycle and the board has no cycle
+pub fn agent_for(mode: AutopilotMode, view: &GameView) -> Option<Box<dyn Agent>> {
+    match mode {
+        AutopilotMode::Path => Some(Box::new(PathBot)),
+        AutopilotMode::Cycle => CycleBot::new(view).map(|bot| Box::new(bot) as Box<dyn Agent>),
+    }
+}
+
+impl fmt::Display for AutopilotMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AutopilotMode::Path => write!(f, "path"),
+            AutopilotMode::Cycle => write!(f, "cycle"),
+        }
+    }
+}
+
+impl std::str::FromStr for AutopilotMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "path" => Ok(AutopilotMode::Path),
+            "cycle" => Ok(AutopilotMode::Cycle),
+            invalid => Err(format!("unknown autopilot '{invalid}', expected path or cycle")),
+        }
+    }
+}
+
+//Picks the next direction: the apple if it is safe, else the tail, else the most room
+pub fn choose_direction(view: &GameView) -> Direction {
+    let grid = Grid::new(view);
+    let body: VecDeque<(i32, i32)> = view
+        .get_body()
+        .iter()
+        .map(|block| (block.x, block.y))
+        .collect();
+    let heading = view.get_heading();
+    let moves: Vec<Direction> = Direction::ALL
+        .into_iter()
+        .filter(|dir| *dir != heading.opposite())
+        .collect();
+
+    if let Some(path) = grid.path(&body, view.get_apple_loc(), Some(&heading.opposite())) {
+        let after = grid.follow(&body, &path, view.get_apple_loc());
+        if grid.reaches_tail(&after) {
+            return path[0].clone();
+        }
+    }
+    //Stall by following the tail, taking the longest way round to leave room to grow
+    let safe = moves
+        .iter()
+        .filter_map(|dir| {
+            let next = grid.step(body[0], dir)?;
+            let after = grid.follow(&body, std::slice::from_ref(dir), view.get_apple_loc());
+            if !grid.is_free(&body, next) || !grid.reaches_tail(&after) {
+                return None;
+            }
+            Some((grid.distance(next, view.get_apple_loc()), dir.clone()))
+        })
+        .max_by_key(|(distance, _)| *distance);
+    if let Some((_, dir)) = safe {
+        return dir;
+    }
+    //No safe move left, survive as long as possible
+    moves
+        .into_iter()
+        .filter_map(|dir| {
+            let next = grid.step(body[0], &dir)?;
+            grid.is_free(&body, next)
+                .then(|| (grid.room(&body, next), dir))
+        })
+        .max_by_key(|(room, _)| *room)
+        .map(|(_, dir)| dir)
+        .unwrap_or(heading)
+}
+
+//The board as the bot sees it: its size, edges and obstacles
+struct Grid<'a> {
+    view: GameView<'a>,
+    size: (i32, i32),
+    obstacles: Vec<bool>,
+}
+
+impl<'a> Grid<'a> {
+    fn new(view: &GameView<'a>) -> Self {
+        let size = view.get_game_size();
+        let obstacles = (0..size.0 * size.1)
+            .map(|i| view.is_obstacle((i % size.0, i / size.0)))
+            .collect();
+        Grid {
+            view: *view,
+            size,
+            obstacles,
+        }
+    }
+
+    fn index(&self, (x, y): (i32, i32)) -> usize {
+        (y * self.size.0 + x) as usize
+    }
+
+    //Where one move in `dir` leads, None into a solid wall
+    fn step(&self, pos: (i32, i32), dir: &Direction) -> Option<(i32, i32)> {
+        self.view.next_position(pos, dir)
+    }
+
+    //Cells the snake blocks on its next move: every part except the tail, which moves away
+    fn blocked(&self, body: &VecDeque<(i32, i32)>) -> Vec<bool> {
+        let mut blocked = self.obstacles.clone();
+        for &pos in body.iter().take(body.len() - 1) {
+            blocked[self.index(pos)] = true;
+        }
+        blocked
+    }
+
+    fn is_free(&self, body: &VecDeque<(i32, i32)>, pos: (i32, i32)) -> bool {
+        !self.blocked(body)[self.index(pos)]
+    }
+
+    //Breadth-first search from the head, returning the directions of a shortest path to `target`.
+    //`back` is a first move the game would reject, i.e. straight back.
+    fn path(
+        &self,
+        body: &VecDeque<(i32, i32)>,
+        target: (i32, i32),
+        back: Option<&Direction>,
+    ) -> Option<Vec<Direction>> {
+        let blocked = self.blocked(body);
+        let mut came_from: Vec<Option<((i32, i32), Direction)>> = vec![None; blocked.len()];
+        let mut seen = vec![false; blocked.len()];
+        let mut queue = VecDeque::from([body[0]]);
+        seen[self.index(body[0])] = true;
+        while let Some(pos) = queue.pop_front() {
+            if pos == target && pos != body[0] {
+                let mut path = Vec::new();
+                let mut at = pos;
+                while let Some((from, dir)) = came_from[self.index(at)].clone() {
+                    path.push(dir);
+                    at = from;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for dir in Direction::ALL {
+                if pos == body[0] && back == Some(&dir) {
+                    continue;
+                }
+                let Some(next) = self.step(pos, &dir) else {
+                    continue;
+                };
+                let i = self.index(next);
+                //The tail is a valid goal even when the snake has just grown over it
+                if seen[i] || (blocked[i] && next != target) {
+                    continue;
+                }
+                seen[i] = true;
+                came_from[i] = Some((pos, dir));
+                queue.push_back(next);
+            }
+        }
+        None
+    }
+
+    //The body after following `path`, growing where it eats the apple
+    fn follow(
+        &self,
+        body: &VecDeque<(i32, i32)>,
+        path: &[Direction],
+        apple: (i32, i32),
+    ) -> VecDeque<(i32, i32)> {
+        let mut body = body.clone();
+        for dir in path {
+            let Some(next) = self.step(body[0], dir) else {
+                break;
+            };
+            body.push_front(next);
+            if next != apple {
+                body.pop_back();
+            }
+        }
+        body
+    }
+
+    //A snake that can reach its tail can always keep moving by following it
+    fn reaches_tail(&self, body: &VecDeque<(i32, i32)>) -> bool {
+        body.len() < 3 || self.path(body, body[body.len() - 1], None).is_some()
+    }
+
+    //Number of moves between two cells on an empty board
+    fn distance(&self, from: (i32, i32), to: (i32, i32)) -> i32 {
+        let (dx, dy) = ((from.0 - to.0).abs(), (from.1 - to.1).abs());
+        match self.view.get_edge_policy() {
+            EdgePolicy::Solid => dx + dy,
+            EdgePolicy::Wrap => dx.min(self.size.0 - dx) + dy.min(self.size.1 - dy),
+        }
+    }
+
+    //Number of free cells reachable from `start`
+    fn room(&self, body: &VecDeque<(i32, i32)>, start: (i32, i32)) -> usize {
+        let mut blocked = self.blocked(body);
+        let mut queue = VecDeque::from([start]);
+        blocked[self.index(start)] = true;
+        let mut count = 0;
+        while let Some(pos) = queue.pop_front() {
+            count += 1;
+            for dir in Direction::ALL {
+                let Some(next) = self.step(pos, &dir) else {
+                    continue;
+                };
+                if !blocked[self.index(next)] {
+                    blocked[self.index(next)] = true;
+                    queue.push_back(next);
+                }
+            }
+        }
+        count
+    }
+}
@@ -0,0 +1,28 @@
+use clap::Parser;
+
+use snake_game::external::ExternalBot;
+use snake_game::tournament::TournamentOptions;
+
+fn main() {
+    //e.g. `cargo run --release --bin tournament -- --games 50 --width 10 --height 10 --edges solid`
+    let options = TournamentOptions::parse();
+    let tournament = match options.build() {
+        Ok(tournament) => tournament,
+        Err(err) => {
+            eprintln!("error: {err}");
+            std::process::exit(2);
+        }
+    };
+    for &mode in &options.bots {
+        match tournament.bot(mode) {
+            Some(bot) => println!("{}", tournament.play(bot)),
+            None => println!("{mode}: cannot play this board"),
+        }
+    }
+    for command in &options.external {
+        match ExternalBot::spawn(command, options.get_bot_timeout(), None) {
+            Ok(bot) => println!("{}", tournament.play(Box::new(bot))),
+            Err(err) => println!("{command}: {err}"),
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use std::fmt;
+
+use piston_window::Key;
+use serde::{Deserialize, Serialize};
+
+use crate::snake::Direction;
+use crate::Game;
+
+//What a key press asks the game to do, independent of the frontend
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    Move(Direction),
+    Pause,
+    Restart,
+    Quit,
+    Save,
+    Load,
+}
+
+//Keys for every action, as piston key names (e.g. "W", "Up", "Space", "Return").
+//Actions missing from the settings file keep their default keys.
+//Restart is only used once the game is over and pause/move only while playing,
+//so the same key may restart and pause; quit and load work in both.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeyBindings {
+    pub up: Vec<Key>,
+    pub down: Vec<Key>,
+    pub left: Vec<Key>,
+    pub right: Vec<Key>,
+    pub pause: Vec<Key>,
+    pub restart: Vec<Key>,
+    pub quit: Vec<Key>,
+    pub save: Vec<Key>,
+    pub load: Vec<Key>,
+}
+
+//A key bound to two actions that can happen at the same time
+#[derive(Debug, Clone, PartialEq)]
+pub struct BindingConflict {
+    pub key: Key,
+    pub first: &'static str,
+    pub second: &'static str,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        KeyBindings {
+            up: vec![Key::W, Key::Up],
+            down: vec![Key::S, Key::Down],
+            left: vec![Key::A, Key::Left],
+            right: vec![Key::D, Key::Right],
+            pause: vec![Key::P, Key::Space],
+            restart: vec![Key::R, Key::Return, Key::Space],
+            quit: vec![Key::Escape],
+            save: vec![Key::F5],
+            load: vec![Key::F9],
+        }
+    }
+}
+
+impl KeyBindings {
+    //Looks up the action of a key, `over` tells if the game has ended
+    pub fn action(&self, key: Key, over: bool) -> Option<Action> {
+        self.actions(over)
+            .into_iter()
+            .find(|(_, keys, _)| keys.contains(&key))
+            .map(|(_, _, action)| action)
+    }
+
+    //Rejects keys bound to two actions that are active at the same time
+    pub fn validate(&self) -> Result<(), BindingConflict> {
+        for over in [false, true] {
+            let actions = self.actions(over);
+            for (i, (first, keys, _)) in actions.iter().enumerate() {
+                for (second, other_keys, _) in &actions[i + 1..] {
+                    if let Some(&key) = keys.iter().find(|key| other_keys.contains(key)) {
+                        return Err(BindingConflict { key, first, second });
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    //private functions
+    fn actions(&self, over: bool) -> Vec<(&'static str, &Vec<Key>, Action)> {
+        if over {
+            return vec![
+                ("quit", &self.quit, Action::Quit),
+                ("load", &self.load, Action::Load),
+                ("restart", &self.restart, Action::Restart),
+            ];
+        }
+        vec![
+            ("quit", &self.quit, Action::Quit),
+            ("save", &self.save, Action::Save),
+            ("load", &self.load, Action::Load),
+            ("pause", &self.pause, Action::Pause),
+            ("up", &self.up, Action::Move(Direction::Up)),
+            ("down", &self.down, Action::Move(Direction::Down)),
+            ("left", &self.left, Action::Move(Direction::Left)),
+            ("right", &self.right, Action::Move(Direction::Right)),
+        ]
+    }
+}
+
+//The part of handling a key press that every frontend shares. Quitting works everywhere, a
+//replay can only be paused, and the autopilot steers while the player can still pause and
+//restart. Returns what is left for the frontend to do, e.g. a move, a save or quitting.
+pub(crate) fn frontend_action(
+    action: Option<Action>,
+    game: &mut Game,
+    replaying: bool,
+    autopilot: bool,
+) -> Option<Action> {
+    match action {
+        Some(Action::Quit) => Some(Action::Quit),
+        //Everything else in a replay comes from the recording
+        Some(Action::Pause) if replaying => {
+            game.toggle_pause();
+            None
+        }
+        _ if replaying => None,
+        Some(Action::Move(_)) if autopilot => None,
+        action => action,
+    }
+}
+
+impl fmt::Display for BindingConflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "key {:?} is bound to both {} and {}",
+            self.key, self.first, self.second
+        )
+    }
+}
+
+impl std::error::Error for BindingConflict {}
@@ -0,0 +1,127 @@
+use rand::Rng;
+
+use crate::snake::Block;
+
+const NOT_FREE: usize = usize::MAX;
+
+//Largest width or height a game can be started with
+pub const MAX_SIDE: i32 = 1000;
+
+//Occupancy grid for the whole board.
+//Keeps a count of snake segments per cell, the static obstacles and a list of
+//the free cells, so collision checks and picking a random free cell are O(1).
+//It is not saved, a loaded game rebuilds it from the snake and the level.
+#[derive(Debug, Clone, Default)]
+pub struct Board {
+    size: (i32, i32),
+    occupants: Vec<u32>,
+    blocked: Vec<bool>,
+    obstacles: Vec<(i32, i32)>,
+    free_cells: Vec<usize>,
+    free_slots: Vec<usize>,
+}
+
+impl Board {
+    //Construction
+    pub fn new(size: (i32, i32)) -> Self {
+        let cells = size.0.max(0) as usize * size.1.max(0) as usize;
+        Board {
+            size,
+            occupants: vec![0; cells],
+            blocked: vec![false; cells],
+            obstacles: Vec::new(),
+            free_cells: (0..cells).collect(),
+            free_slots: (0..cells).collect(),
+        }
+    }
+    pub fn from_body<'a>(size: (i32, i32), body: impl IntoIterator<Item = &'a Block>) -> Self {
+        let mut board = Board::new(size);
+        for block in body {
+            board.occupy((block.x, block.y));
+        }
+        board
+    }
+
+    //Occupancy
+    pub fn add_obstacle(&mut self, pos: (i32, i32)) {
+        let Some(cell) = self.index(pos) else { return };
+        if self.blocked[cell] {
+            return;
+        }
+        self.blocked[cell] = true;
+        self.obstacles.push(pos);
+        self.remove_free(cell);
+    }
+    pub fn occupy(&mut self, pos: (i32, i32)) {
+        let Some(cell) = self.index(pos) else { return };
+        self.remove_free(cell);
+        self.occupants[cell] += 1;
+    }
+    pub fn vacate(&mut self, pos: (i32, i32)) {
+        let Some(cell) = self.index(pos) else { return };
+        if self.occupants[cell] == 0 {
+            return;
+        }
+        self.occupants[cell] -= 1;
+        if self.occupants[cell] == 0 && !self.blocked[cell] {
+            self.free_slots[cell] = self.free_cells.len();
+            self.free_cells.push(cell);
+        }
+    }
+
+    //Getters
+    pub fn get_size(&self) -> (i32, i32) {
+        self.size
+    }
+    pub fn occupants(&self, pos: (i32, i32)) -> u32 {
+        self.index(pos).map_or(0, |cell| self.occupants[cell])
+    }
+    pub fn is_obstacle(&self, pos: (i32, i32)) -> bool {
+        self.index(pos).is_some_and(|cell| self.blocked[cell])
+    }
+    pub fn get_obstacles(&self) -> &[(i32, i32)] {
+        &self.obstacles
+    }
+    //Cells the snake could ever cover, i.e. everything but obstacles
+    pub fn open_cells(&self) -> usize {
+        self.occupants.len() - self.obstacles.len()
+    }
+    pub fn is_free(&self, pos: (i32, i32)) -> bool {
+        self.index(pos).is_some_and(|cell| self.free_slots[cell] != NOT_FREE)
+    }
+    pub fn free_count(&self) -> usize {
+        self.free_cells.len()
+    }
+    pub fn random_free_cell<R: Rng>(&self, rng: &mut R) -> Option<(i32, i32)> {
+        if self.free_cells.is_empty() {
+            return None;
+        }
+        let cell = self.free_cells[rng.gen_range(0..self.free_cells.len())];
+        Some(self.position(cell))
+    }
+
+    //private functions
+    fn index(&self, pos: (i32, i32)) -> Option<usize> {
+        let (x, y) = pos;
+        if x < 0 || y < 0 || x >= self.size.0 || y >= self.size.1 {
+            return None;
+        }
+        Some(y as usize * self.size.0 as usize + x as usize)
+    }
+    fn position(&self, cell: usize) -> (i32, i32) {
+        let width = self.size.0 as usize;
+        ((cell % width) as i32, (cell / width) as i32)
+    }
+    //Swap-remove the cell from the free list in constant time
+    fn remove_free(&mut self, cell: usize) {
+        let slot = self.free_slots[cell];
+        if slot == NOT_FREE {
+            return;
+        }
+        let last = self.free_cells.len() - 1;
+        self.free_cells.swap(slot, last);
+        self.free_slots[self.free_cells[slot]] = slot;
+        self.free_cells.pop();
+        self.free_slots[cell] = NOT_FREE;
+    }
+}
@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+
+pub const STEP_TIME: f64 = 0.2; // in second
+
+//Fixed-timestep driver: turns wall-clock frame times into whole game ticks.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Clock {
+    step_time: f64,
+    interval: f64,
+}
+
+impl Clock {
+    //Construction
+    pub fn new(step_time: f64, interval: f64) -> Self {
+        Clock {
+            step_time,
+            interval,
+        }
+    }
+
+    //Returns true when enough time has passed for the next tick.
+    //At most one tick is produced per call, so a long frame never makes the snake jump.
+    pub fn advance(&mut self, delta_time: f64) -> bool {
+        self.interval -= delta_time;
+        if self.interval <= 0.0 {
+            self.interval = self.step_time;
+            return true;
+        }
+        false
+    }
+
+    //The new step time is used from the next tick on
+    pub fn set_step_time(&mut self, step_time: f64) {
+        self.step_time = step_time;
+    }
+
+    //Getters
+    pub fn get_step_time(&self) -> f64 {
+        self.step_time
+    }
+    pub fn get_interval(&self) -> f64 {
+        self.interval
+    }
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Clock::new(STEP_TIME, 0.0)
+    }
+}
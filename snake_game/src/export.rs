@@ -0,0 +1,105 @@
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::framebuffer::Framebuffer;
+use crate::render::{border_width, window_size};
+use crate::replay::{Player, Replay};
+use crate::settings::Colours;
+use crate::GameState;
+
+//How long the last frame stays up before the animation loops, in hundredths of a second
+const FINAL_FRAME_DELAY: u16 = 200;
+
+//Quality of the colour reduction for frames with more than 256 colours, 1 is best and 30 fastest
+const QUANTIZE_SPEED: i32 = 10;
+
+#[derive(Debug)]
+pub enum ExportError {
+    Io(io::Error),
+    Encode(gif::EncodingError),
+    //GIF sizes are 16 bit
+    TooLarge { width: u32, height: u32 },
+}
+
+//Renders every tick of a recorded game into a looping GIF, each frame shown until the game's
+//clock would make the next tick. Returns the number of frames.
+pub fn export_gif(replay: &Replay, colours: &Colours, path: &Path) -> Result<usize, ExportError> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(ExportError::Io)?;
+    }
+    let file = File::create(path).map_err(ExportError::Io)?;
+    write_gif(replay, colours, BufWriter::new(file))
+}
+
+pub fn write_gif<W: Write>(
+    replay: &Replay,
+    colours: &Colours,
+    out: W,
+) -> Result<usize, ExportError> {
+    let mut game = replay.start_game();
+    //Checked before any frame is drawn, a frame that large might not even fit in memory
+    let [width, height] = window_size(game.get_game_size(), border_width(game.get_edge_policy()));
+    let too_large = ExportError::TooLarge { width, height };
+    let (Ok(width), Ok(height)) = (u16::try_from(width), u16::try_from(height)) else {
+        return Err(too_large);
+    };
+    let mut encoder = gif::Encoder::new(out, width, height, &[]).map_err(ExportError::Encode)?;
+    encoder
+        .set_repeat(gif::Repeat::Infinite)
+        .map_err(ExportError::Encode)?;
+
+    //Same loop as Replay::verify, drawing the game before every tick
+    let mut player = Player::new(replay.clone());
+    let mut frames = 0;
+    loop {
+        player.feed(&mut game);
+        //A replay whose game goes on past its recorded end stops there, like in Replay::verify
+        let done = game.is_over()
+            || game.get_state() == GameState::Waiting
+            || game.get_ticks() >= replay.get_ticks();
+        let delay = if done {
+            FINAL_FRAME_DELAY
+        } else {
+            (game.get_clock().get_interval() * 100.0).round() as u16
+        };
+        let mut pixels = Framebuffer::snapshot(&game, colours, "")
+            .get_pixels()
+            .to_vec();
+        let mut frame = gif::Frame::from_rgba_speed(width, height, &mut pixels, QUANTIZE_SPEED);
+        //The first tick comes right away, but a delay of 0 is played slowly by some viewers
+        frame.delay = delay.max(1);
+        encoder.write_frame(&frame).map_err(ExportError::Encode)?;
+        frames += 1;
+        if done {
+            return Ok(frames);
+        }
+        //After a speed-up the clock may need more than one interval to reach the next tick. An
+        //eaten apple is grown before drawing, which ends the game once the board is full.
+        let tick = game.get_ticks();
+        loop {
+            match game.get_state() {
+                GameState::Moving(_) if game.get_ticks() == tick => {}
+                GameState::AteApple => {}
+                _ => break,
+            }
+            game.update(game.get_clock().get_interval());
+        }
+    }
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExportError::Io(err) => write!(f, "could not write animation: {err}"),
+            ExportError::Encode(err) => write!(f, "could not encode GIF: {err}"),
+            ExportError::TooLarge { width, height } => write!(
+                f,
+                "animation would be {width}x{height} pixels, GIFs can be at most 65535x65535"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ExportError {}
@@ -0,0 +1,271 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, LineWriter, Write};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender, TrySendError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::agent::{Agent, GameView};
+use crate::snake::Direction;
+use crate::GameState;
+
+//How long a bot may think about a move unless told otherwise
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_millis(100);
+
+//Requests that may wait for a bot that has stopped reading, on top of what the pipe holds
+const PENDING_REQUESTS: usize = 16;
+
+//A bot in another program, e.g. `python3 bots/greedy.py`. Every tick it gets the game as one line
+//of JSON on its standard input and answers with one line like {"move": "up"} on its standard
+//output. Whenever it does not answer in time, answers something invalid or turns straight back,
+//the snake keeps its heading for that tick.
+pub struct ExternalBot {
+    command: String,
+    child: Child,
+    requests: SyncSender<String>,
+    replies: Receiver<String>,
+    timeout: Duration,
+    //Requests without an answer yet, answers that come too late are skipped
+    unanswered: usize,
+    log: Option<LineWriter<File>>,
+    exited: bool,
+    timeouts: u32,
+    illegal_moves: u32,
+}
+
+#[derive(Debug)]
+pub enum BotError {
+    EmptyCommand,
+    Spawn { program: String, error: io::Error },
+    Log(io::Error),
+}
+
+//The line sent every tick, e.g.
+//{"tick":0,"state":"waiting","size":[20,20],"edges":"wrap","heading":"right",
+// "body":[[10,10],[9,10],[8,10]],"apple":[3,4],"points":0,"obstacles":[]}
+#[derive(Debug, Serialize)]
+struct Request<'a> {
+    tick: u64,
+    state: &'static str,
+    size: (i32, i32),
+    edges: String,
+    heading: String,
+    //From head to tail
+    body: Vec<(i32, i32)>,
+    apple: (i32, i32),
+    points: i32,
+    obstacles: &'a [(i32, i32)],
+}
+
+#[derive(Debug, Deserialize)]
+struct Reply {
+    #[serde(rename = "move")]
+    dir: String,
+}
+
+impl ExternalBot {
+    //Construction
+    //Starts `command`, split at whitespace into the program and its arguments. With `log` every
+    //line sent and received goes into that file, together with what went wrong.
+    pub fn spawn(command: &str, timeout: Duration, log: Option<&Path>) -> Result<Self, BotError> {
+        let mut words = command.split_whitespace();
+        let program = words.next().ok_or(BotError::EmptyCommand)?;
+        let log = match log {
+            Some(path) => Some(LineWriter::new(File::create(path).map_err(BotError::Log)?)),
+            None => None,
+        };
+        let mut child = Command::new(program)
+            .args(words)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|error| BotError::Spawn {
+                program: program.to_string(),
+                error,
+            })?;
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = child.stdout.take().expect("stdout is piped");
+        //Writing and reading happen on their own threads so a stuck bot cannot block the game
+        let (requests, pending) = mpsc::sync_channel::<String>(PENDING_REQUESTS);
+        thread::spawn(move || {
+            let mut stdin = stdin;
+            for line in pending {
+                if writeln!(stdin, "{line}").and_then(|_| stdin.flush()).is_err() {
+                    break;
+                }
+            }
+        });
+        let (sender, replies) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else { break };
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(ExternalBot {
+            command: command.to_string(),
+            child,
+            requests,
+            replies,
+            timeout,
+            unanswered: 0,
+            log,
+            exited: false,
+            timeouts: 0,
+            illegal_moves: 0,
+        })
+    }
+
+    //Getters
+    //Moves the bot did not make in time
+    pub fn get_timeouts(&self) -> u32 {
+        self.timeouts
+    }
+    //Answers that were not a valid move
+    pub fn get_illegal_moves(&self) -> u32 {
+        self.illegal_moves
+    }
+
+    //private functions
+    //Hands the game to the writer thread, false if the bot cannot take it
+    fn send(&mut self, view: &GameView) -> bool {
+        let request = Request::from_view(view);
+        let line = serde_json::to_string(&request).expect("requests are valid JSON");
+        self.write_log('>', &line);
+        match self.requests.try_send(line) {
+            Ok(()) => true,
+            Err(TrySendError::Full(_)) => {
+                self.timeouts += 1;
+                self.write_log('!', "the bot is not reading its input");
+                false
+            }
+            Err(TrySendError::Disconnected(_)) => {
+                self.exited = true;
+                self.write_log('!', "the bot has exited");
+                false
+            }
+        }
+    }
+    //The answer to the latest request, None if it did not come in time
+    fn receive(&mut self) -> Option<String> {
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            let wait = deadline.saturating_duration_since(Instant::now());
+            match self.replies.recv_timeout(wait) {
+                Ok(line) => {
+                    self.unanswered -= 1;
+                    self.write_log('<', &line);
+                    if self.unanswered == 0 {
+                        return Some(line);
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    self.timeouts += 1;
+                    let millis = self.timeout.as_millis();
+                    self.write_log('!', &format!("no move within {millis} ms"));
+                    return None;
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    self.exited = true;
+                    self.write_log('!', "the bot has exited");
+                    return None;
+                }
+            }
+        }
+    }
+    //The move in `line`, None if it is not one the snake can make
+    fn parse(&mut self, line: &str, view: &GameView) -> Option<Direction> {
+        let parsed = serde_json::from_str::<Reply>(line)
+            .map_err(|err| err.to_string())
+            .and_then(|reply| reply.dir.parse::<Direction>());
+        let problem = match parsed {
+            Ok(dir) if view.get_body().len() > 1 && dir == view.get_heading().opposite() => {
+                format!("{dir} turns straight back")
+            }
+            Ok(dir) => return Some(dir),
+            Err(err) => err,
+        };
+        self.illegal_moves += 1;
+        self.write_log('!', &format!("illegal move: {problem}"));
+        None
+    }
+    fn write_log(&mut self, mark: char, line: &str) {
+        if let Some(log) = self.log.as_mut() {
+            //A broken log is not worth stopping the game for
+            let _ = writeln!(log, "{mark} {line}");
+        }
+    }
+}
+
+impl Agent for ExternalBot {
+    fn name(&self) -> &str {
+        &self.command
+    }
+    fn choose(&mut self, view: &GameView) -> Direction {
+        let heading = view.get_heading();
+        if self.exited {
+            return heading;
+        }
+        if !self.send(view) {
+            return heading;
+        }
+        self.unanswered += 1;
+        self.receive()
+            .and_then(|line| self.parse(&line, view))
+            .unwrap_or(heading)
+    }
+}
+
+impl Drop for ExternalBot {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+impl<'a> Request<'a> {
+    fn from_view(view: &GameView<'a>) -> Self {
+        let state = match view.get_state() {
+            GameState::Waiting => "waiting",
+            GameState::Moving(_) => "moving",
+            GameState::Paused(_) => "paused",
+            GameState::AteApple => "eating",
+            GameState::Dead => "dead",
+            GameState::Won => "won",
+        };
+        Request {
+            tick: view.get_ticks(),
+            state,
+            size: view.get_game_size(),
+            edges: view.get_edge_policy().to_string(),
+            heading: view.get_heading().to_string(),
+            body: view
+                .get_body()
+                .iter()
+                .map(|block| (block.x, block.y))
+                .collect(),
+            apple: view.get_apple_loc(),
+            points: view.get_points(),
+            obstacles: view.get_obstacles(),
+        }
+    }
+}
+
+impl fmt::Display for BotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BotError::EmptyCommand => write!(f, "the bot command is empty"),
+            BotError::Spawn { program, error } => write!(f, "could not start {program}: {error}"),
+            BotError::Log(err) => write!(f, "could not open the bot log: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for BotError {}
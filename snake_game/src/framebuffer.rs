@@ -0,0 +1,229 @@
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufWriter};
+use std::path::Path;
+use std::sync::OnceLock;
+
+use rusttype::{point, Font, Scale};
+
+use crate::render::{border_width, window_size, Cell, Color, Renderer, BLOCK_SIZE};
+use crate::settings::Colours;
+use crate::Game;
+
+//The window's font, built in so snapshots look the same wherever they are made
+static FONT_DATA: &[u8] = include_bytes!("../assets/retro-gaming.ttf");
+
+//An RGBA image drawn in software at BLOCK_SIZE, for snapshot tests and image export
+//without a window or a GPU
+#[derive(Debug, Clone, PartialEq)]
+pub struct Framebuffer {
+    width: u32,
+    height: u32,
+    //Rows of RGBA pixels from the top-left corner, 4 bytes per pixel
+    pixels: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub enum ImageError {
+    Io(io::Error),
+    Encode(png::EncodingError),
+    Decode(png::DecodingError),
+    UnsupportedFormat {
+        colour_type: png::ColorType,
+        bit_depth: png::BitDepth,
+    },
+}
+
+impl Framebuffer {
+    //Construction
+    //A transparent image, `width` by `height` pixels
+    pub fn new(width: u32, height: u32) -> Self {
+        Framebuffer {
+            width,
+            height,
+            pixels: vec![0; width as usize * height as usize * 4],
+        }
+    }
+    //Large enough for the game and its HUD, like the window at scale 1
+    pub fn for_game(game: &Game) -> Self {
+        let [width, height] =
+            window_size(game.get_game_size(), border_width(game.get_edge_policy()));
+        Framebuffer::new(width, height)
+    }
+    //Draws one frame of the game, e.g. `Framebuffer::snapshot(&game, &Colours::default(), "")`
+    pub fn snapshot(game: &Game, colours: &Colours, resume_hint: &str) -> Self {
+        let mut framebuffer = Framebuffer::for_game(game);
+        game.draw(&mut framebuffer, colours, resume_hint);
+        framebuffer
+    }
+
+    //Files
+    pub fn to_png(&self) -> Result<Vec<u8>, ImageError> {
+        let mut data = Vec::new();
+        self.write_png(&mut data)?;
+        Ok(data)
+    }
+    pub fn save_png(&self, path: &Path) -> Result<(), ImageError> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(ImageError::Io)?;
+        }
+        let file = File::create(path).map_err(ImageError::Io)?;
+        self.write_png(BufWriter::new(file))
+    }
+    //Reads 8-bit RGBA or RGB images, like the ones written by save_png()
+    pub fn from_png(data: &[u8]) -> Result<Framebuffer, ImageError> {
+        let mut reader = png::Decoder::new(data)
+            .read_info()
+            .map_err(ImageError::Decode)?;
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buffer).map_err(ImageError::Decode)?;
+        buffer.truncate(info.buffer_size());
+        let pixels = match (info.color_type, info.bit_depth) {
+            (png::ColorType::Rgba, png::BitDepth::Eight) => buffer,
+            (png::ColorType::Rgb, png::BitDepth::Eight) => buffer
+                .chunks(3)
+                .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], 255])
+                .collect(),
+            (colour_type, bit_depth) => {
+                return Err(ImageError::UnsupportedFormat {
+                    colour_type,
+                    bit_depth,
+                })
+            }
+        };
+        Ok(Framebuffer {
+            width: info.width,
+            height: info.height,
+            pixels,
+        })
+    }
+    pub fn load_png(path: &Path) -> Result<Framebuffer, ImageError> {
+        let data = fs::read(path).map_err(ImageError::Io)?;
+        Framebuffer::from_png(&data)
+    }
+
+    //Number of pixels where a channel differs by more than `tolerance`, every pixel if the sizes differ
+    pub fn count_differences(&self, other: &Framebuffer, tolerance: u8) -> usize {
+        if (self.width, self.height) != (other.width, other.height) {
+            return self.pixels.len().max(other.pixels.len()) / 4;
+        }
+        self.pixels
+            .chunks(4)
+            .zip(other.pixels.chunks(4))
+            .filter(|(a, b)| {
+                a.iter()
+                    .zip(b.iter())
+                    .any(|(a, b)| a.abs_diff(*b) > tolerance)
+            })
+            .count()
+    }
+
+    //Getters
+    pub fn get_size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+    pub fn get_pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let i = (y as usize * self.width as usize + x as usize) * 4;
+        [
+            self.pixels[i],
+            self.pixels[i + 1],
+            self.pixels[i + 2],
+            self.pixels[i + 3],
+        ]
+    }
+    pub fn get_pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    //private functions
+    fn write_png<W: io::Write>(&self, out: W) -> Result<(), ImageError> {
+        let mut encoder = png::Encoder::new(out, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(ImageError::Encode)?;
+        writer
+            .write_image_data(&self.pixels)
+            .map_err(ImageError::Encode)?;
+        writer.finish().map_err(ImageError::Encode)
+    }
+    //Draws `colour` over the pixel, with `coverage` of 0.0 to 1.0 for anti-aliased edges
+    fn blend(&mut self, x: i64, y: i64, colour: Color, coverage: f32) {
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return;
+        }
+        let i = (y as usize * self.width as usize + x as usize) * 4;
+        let alpha = colour[3] * coverage;
+        let under = self.pixels[i + 3] as f32 / 255.0;
+        let out_alpha = alpha + under * (1.0 - alpha);
+        if out_alpha <= 0.0 {
+            return;
+        }
+        for (c, src) in colour.iter().take(3).enumerate() {
+            let dst = self.pixels[i + c] as f32 / 255.0;
+            let value = (src * alpha + dst * under * (1.0 - alpha)) / out_alpha;
+            self.pixels[i + c] = to_byte(value);
+        }
+        self.pixels[i + 3] = to_byte(out_alpha);
+    }
+}
+
+impl Renderer for Framebuffer {
+    fn clear(&mut self, colour: Color) {
+        let pixel = colour.map(to_byte);
+        for chunk in self.pixels.chunks_mut(4) {
+            chunk.copy_from_slice(&pixel);
+        }
+    }
+    fn draw_cell(&mut self, _cell: Cell, colour: Color, x: i32, y: i32) {
+        let size = BLOCK_SIZE as i64;
+        let (left, top) = (x as i64 * size, y as i64 * size);
+        for py in top..top + size {
+            for px in left..left + size {
+                self.blend(px, py, colour, 1.0);
+            }
+        }
+    }
+    fn draw_text(&mut self, colour: Color, size: u32, pos: (f64, f64), text: &str) {
+        //Sizes are in points like in the window, which draws 1.333 pixels per point
+        let scale = Scale::uniform((size as f32 * 1.333).round());
+        let origin = point((pos.0 * BLOCK_SIZE) as f32, (pos.1 * BLOCK_SIZE) as f32);
+        for glyph in font().layout(text, scale, origin) {
+            let Some(bounds) = glyph.pixel_bounding_box() else {
+                continue;
+            };
+            glyph.draw(|gx, gy, coverage| {
+                let x = bounds.min.x as i64 + gx as i64;
+                let y = bounds.min.y as i64 + gy as i64;
+                self.blend(x, y, colour, coverage);
+            });
+        }
+    }
+}
+
+fn font() -> &'static Font<'static> {
+    static FONT: OnceLock<Font<'static>> = OnceLock::new();
+    FONT.get_or_init(|| Font::try_from_bytes(FONT_DATA).expect("the built-in font is valid"))
+}
+
+fn to_byte(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ImageError::Io(err) => write!(f, "could not access image: {err}"),
+            ImageError::Encode(err) => write!(f, "could not write PNG: {err}"),
+            ImageError::Decode(err) => write!(f, "invalid PNG: {err}"),
+            ImageError::UnsupportedFormat {
+                colour_type,
+                bit_depth,
+            } => write!(
+                f,
+                "PNG is {colour_type:?} with {bit_depth:?} bits, expected 8-bit RGB or RGBA"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ImageError {}
@@ -0,0 +1,218 @@
+use crate::agent::{Agent, GameView};
+use crate::autopilot::choose_direction;
+use crate::snake::{Direction, EdgePolicy};
+use crate::GameState;
+
+//A route through every cell of the board that ends where it started. A snake that only ever
+//moves to the next cell on it can never hit itself, so it fills the board. To get there faster
+//the bot skips ahead along the cycle while the snake is short, but never past its own tail.
+//Boards with an odd number of cells only have one with wrapping edges.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HamiltonCycle {
+    size: (i32, i32),
+    //Every cell in the order they are visited
+    cycle: Vec<(i32, i32)>,
+    //Position on the cycle of every cell, indexed by y * width + x
+    order: Vec<usize>,
+}
+
+//Plays along a HamiltonCycle once it has got the snake onto it. On a board without a cycle,
+//e.g. a loaded level with obstacles, it plays like the path bot.
+#[derive(Debug, Clone)]
+pub struct CycleBot {
+    cycle: Option<HamiltonCycle>,
+    //Whether the snake has reached the cycle, it has to get there first
+    on_cycle: bool,
+}
+
+impl CycleBot {
+    //None if the board has no cycle
+    pub fn new(view: &GameView) -> Option<Self> {
+        Some(CycleBot {
+            cycle: Some(HamiltonCycle::new(view)?),
+            on_cycle: false,
+        })
+    }
+}
+
+impl Agent for CycleBot {
+    fn name(&self) -> &str {
+        "cycle"
+    }
+    fn choose(&mut self, view: &GameView) -> Direction {
+        let Some(cycle) = &self.cycle else {
+            return choose_direction(view);
+        };
+        //A restarted game starts off the cycle again
+        if view.get_state() == GameState::Waiting {
+            self.on_cycle = false;
+        }
+        self.on_cycle = self.on_cycle || cycle.lies_on(view);
+        match self.on_cycle {
+            true => cycle.choose_direction(view),
+            false => cycle.join(view),
+        }
+    }
+    fn reset(&mut self, view: &GameView) {
+        self.cycle = HamiltonCycle::new(view);
+        self.on_cycle = false;
+    }
+}
+
+impl HamiltonCycle {
+    //Construction
+    //A cycle for the board, if possible one the snake already lies on. None if the board has
+    //obstacles or no cycle exists: with solid edges a board needs an even number of cells.
+    pub fn new(view: &GameView) -> Option<Self> {
+        if !view.get_obstacles().is_empty() {
+            return None;
+        }
+        let size = view.get_game_size();
+        let edges = view.get_edge_policy();
+        let mut candidates = Vec::new();
+        if let Some(cycle) = rows_cycle(size, edges) {
+            candidates.push(cycle);
+        }
+        //Boards that only work column by column are built transposed
+        if let Some(cycle) = rows_cycle((size.1, size.0), edges) {
+            candidates.push(cycle.into_iter().map(|(x, y)| (y, x)).collect());
+        }
+        //Any mirror image or the reverse is a cycle too, one of them usually fits the snake
+        let mut variants = Vec::new();
+        for cycle in candidates {
+            for flip_x in [false, true] {
+                for flip_y in [false, true] {
+                    for reverse in [false, true] {
+                        let mut cells: Vec<(i32, i32)> = cycle
+                            .iter()
+                            .map(|&(x, y)| {
+                                let x = if flip_x { size.0 - 1 - x } else { x };
+                                let y = if flip_y { size.1 - 1 - y } else { y };
+                                (x, y)
+                            })
+                            .collect();
+                        if reverse {
+                            cells.reverse();
+                        }
+                        variants.push(HamiltonCycle::from_cells(size, cells));
+                    }
+                }
+            }
+        }
+        let fitting = variants.iter().position(|cycle| cycle.lies_on(view));
+        match fitting {
+            Some(i) => Some(variants.swap_remove(i)),
+            None => variants.into_iter().next(),
+        }
+    }
+
+    //True if every part of the snake follows the one behind it on the cycle. From then on
+    //choose_direction() is safe; before, join() gets the snake there.
+    pub fn lies_on(&self, view: &GameView) -> bool {
+        let body: Vec<(i32, i32)> = view
+            .get_body()
+            .iter()
+            .map(|block| (block.x, block.y))
+            .collect();
+        //A snake that has just eaten has its tail twice
+        body.windows(2)
+            .all(|pair| pair[0] == pair[1] || self.distance(pair[1], pair[0]) == 1)
+    }
+
+    //Moves onto the cycle: its next cell if that is free, else whatever the path bot would do.
+    //Once the head has followed the cycle for the length of the snake, the body lies on it too.
+    pub fn join(&self, view: &GameView) -> Direction {
+        let body = view.get_body();
+        let head = view.get_head_pos();
+        let next = self.cycle[(self.position(head) + 1) % self.cycle.len()];
+        let free = !body.iter().any(|block| (block.x, block.y) == next);
+        match direction(view, head, next) {
+            Some(dir) if free && (body.len() == 1 || dir != view.get_heading().opposite()) => dir,
+            _ => choose_direction(view),
+        }
+    }
+
+    //The next direction for a snake that lies on the cycle: the next cell on it, or a shortcut
+    //towards the apple
+    pub fn choose_direction(&self, view: &GameView) -> Direction {
+        let head = view.get_head_pos();
+        let tail = view.get_tail_pos();
+        let apple = view.get_apple_loc();
+        let cells = self.cycle.len();
+        let length = view.get_body().len();
+
+        let next = self.cycle[(self.position(head) + 1) % cells];
+        let mut best = (1, direction(view, head, next).unwrap_or(view.get_heading()));
+        //Cells up to the tail are free, a snake of length 1 has the whole cycle
+        let room = match self.distance(head, tail) {
+            0 => cells,
+            room => room,
+        };
+        let to_apple = self.distance(head, apple);
+        for dir in Direction::ALL {
+            let Some(target) = view.next_position(head, &dir) else {
+                continue;
+            };
+            let ahead = self.distance(head, target);
+            if ahead <= best.0 || ahead >= room || ahead > to_apple {
+                continue;
+            }
+            //Only while the snake and the cells it skips take up at most half the board
+            let grows = usize::from(target == apple);
+            let span = self.distance(tail, target) + 1 + grows;
+            if length > 1 && span > cells / 2 {
+                continue;
+            }
+            best = (ahead, dir);
+        }
+        best.1
+    }
+
+    //Getters
+    pub fn get_cycle(&self) -> &[(i32, i32)] {
+        &self.cycle
+    }
+
+    //private functions
+    fn from_cells(size: (i32, i32), cycle: Vec<(i32, i32)>) -> Self {
+        let mut order = vec![0; cycle.len()];
+        for (position, &(x, y)) in cycle.iter().enumerate() {
+            order[(y * size.0 + x) as usize] = position;
+        }
+        HamiltonCycle { size, cycle, order }
+    }
+    fn position(&self, (x, y): (i32, i32)) -> usize {
+        self.order[(y * self.size.0 + x) as usize]
+    }
+    //Number of steps along the cycle from one cell to another
+    fn distance(&self, from: (i32, i32), to: (i32, i32)) -> usize {
+        let cells = self.cycle.len();
+        (self.position(to) + cells - self.position(from)) % cells
+    }
+}
+
+//Row by row through columns 1 to width - 1, then back up column 0. Ends next to the start if the
+//number of rows is even, or through the wrapped edge from the last column otherwise.
+fn rows_cycle((width, height): (i32, i32), edges: EdgePolicy) -> Option<Vec<(i32, i32)>> {
+    let closes = height % 2 == 0 || edges == EdgePolicy::Wrap || width == 2;
+    if width < 2 || height < 1 || !closes || (edges == EdgePolicy::Solid && height < 2) {
+        return None;
+    }
+    let mut cycle = Vec::with_capacity((width * height) as usize);
+    for y in 0..height {
+        if y % 2 == 0 {
+            cycle.extend((1..width).map(|x| (x, y)));
+        } else {
+            cycle.extend((1..width).rev().map(|x| (x, y)));
+        }
+    }
+    cycle.extend((0..height).rev().map(|y| (0, y)));
+    Some(cycle)
+}
+
+//The move from one cell to a neighbouring one
+fn direction(view: &GameView, from: (i32, i32), to: (i32, i32)) -> Option<Direction> {
+    Direction::ALL
+        .into_iter()
+        .find(|dir| view.next_position(from, dir) == Some(to))
+}
@@ -0,0 +1,139 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::Game;
+
+//How many runs the table keeps
+pub const MAX_ENTRIES: usize = 10;
+pub const MAX_INITIALS: usize = 3;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScoreEntry {
+    pub initials: String,
+    pub score: i32,
+    pub length: usize,
+    pub board: (i32, i32),
+    pub date: String,
+    pub mode: String,
+}
+
+//Top runs, best first. Stored as one tab-separated line per entry:
+//initials, score, length, board (WxH), date (YYYY-MM-DD), mode
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HighScores {
+    entries: Vec<ScoreEntry>,
+}
+
+impl ScoreEntry {
+    pub fn from_game(game: &Game, initials: &str, date: &str) -> Self {
+        let mode = match game.get_level() {
+            Some(level) => level.get_name().to_string(),
+            None => game.get_edge_policy().to_string(),
+        };
+        ScoreEntry {
+            initials: initials.to_string(),
+            score: game.get_points(),
+            length: game.get_snake().get_length(),
+            board: game.get_game_size(),
+            date: date.to_string(),
+            mode,
+        }
+    }
+
+    fn to_line(&self) -> String {
+        format!(
+            "{}\t{}\t{}\t{}x{}\t{}\t{}",
+            self.initials, self.score, self.length, self.board.0, self.board.1, self.date, self.mode
+        )
+    }
+    fn from_line(line: &str) -> Option<ScoreEntry> {
+        let mut fields = line.split('\t');
+        let initials = fields.next()?.to_string();
+        let score = fields.next()?.parse().ok()?;
+        let length = fields.next()?.parse().ok()?;
+        let (width, height) = fields.next()?.split_once('x')?;
+        let board = (width.parse().ok()?, height.parse().ok()?);
+        let date = fields.next()?.to_string();
+        let mode = fields.next()?.to_string();
+        Some(ScoreEntry { initials, score, length, board, date, mode })
+    }
+}
+
+impl HighScores {
+    //Construction
+    //A missing file is just an empty table; unreadable lines are skipped
+    pub fn load(path: &Path) -> io::Result<HighScores> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(HighScores::default()),
+            Err(err) => return Err(err),
+        };
+        let mut scores = HighScores::default();
+        for entry in text.lines().filter_map(ScoreEntry::from_line) {
+            scores.insert(entry);
+        }
+        Ok(scores)
+    }
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let lines: Vec<String> = self.entries.iter().map(ScoreEntry::to_line).collect();
+        fs::write(path, lines.join("\n") + "\n")
+    }
+    //<data dir>/snake_game/highscores.txt, e.g. ~/.local/share on Linux
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("snake_game").join("highscores.txt"))
+    }
+
+    //Table logic
+    pub fn qualifies(&self, score: i32) -> bool {
+        if score <= 0 {
+            return false;
+        }
+        self.entries.len() < MAX_ENTRIES
+            || self.entries.last().is_some_and(|lowest| score > lowest.score)
+    }
+    //Returns the rank (0 is best) the entry got, or None if it did not make the table.
+    //Ties keep the older run first.
+    pub fn insert(&mut self, entry: ScoreEntry) -> Option<usize> {
+        let rank = self.entries.iter().position(|other| entry.score > other.score);
+        let rank = rank.unwrap_or(self.entries.len());
+        if rank >= MAX_ENTRIES {
+            return None;
+        }
+        self.entries.insert(rank, entry);
+        self.entries.truncate(MAX_ENTRIES);
+        Some(rank)
+    }
+
+    //Getters
+    pub fn get_entries(&self) -> &[ScoreEntry] {
+        &self.entries
+    }
+}
+
+//Today's date (UTC) as YYYY-MM-DD
+pub fn today() -> String {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    let (year, month, day) = civil_from_days((seconds / 86_400) as i64);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+//Days since 1970-01-01 to a (year, month, day) date, after Howard Hinnant's civil_from_days
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
@@ -0,0 +1,173 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::board::MAX_SIDE;
+use crate::snake::Direction;
+
+//Length of the snake at the start of a level, trailing behind the head
+pub const START_LENGTH: i32 = 3;
+
+//A level is a plain-text ASCII grid, one line per row:
+//  #        obstacle
+//  . or ' ' empty cell
+//  ^ v < >  snake head, pointing in its starting heading
+//Lines starting with ';' are comments. Every row must have the same width.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Level {
+    name: String,
+    size: (i32, i32),
+    obstacles: Vec<(i32, i32)>,
+    start: (i32, i32),
+    heading: Direction,
+}
+
+#[derive(Debug)]
+pub enum LevelError {
+    Io(std::io::Error),
+    Empty,
+    RaggedRow { row: usize, width: usize, expected: usize },
+    UnknownTile { row: usize, col: usize, tile: char },
+    MissingSnake,
+    MultipleSnakes { row: usize, col: usize },
+    SnakeBlocked,
+    TooLarge { width: usize, height: usize },
+}
+
+impl Level {
+    //Construction
+    pub fn load(path: &Path) -> Result<Level, LevelError> {
+        let text = fs::read_to_string(path).map_err(LevelError::Io)?;
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        Level::parse(&name, &text)
+    }
+    pub fn parse(name: &str, text: &str) -> Result<Level, LevelError> {
+        let rows: Vec<&str> = text
+            .lines()
+            .map(|line| line.trim_end_matches('\r'))
+            .filter(|line| !line.starts_with(';'))
+            .collect();
+        //Allow trailing blank lines at the end of the file
+        let rows = match rows.iter().rposition(|row| !row.trim().is_empty()) {
+            Some(last) => &rows[..=last],
+            None => return Err(LevelError::Empty),
+        };
+
+        let expected = rows[0].chars().count();
+        if expected > MAX_SIDE as usize || rows.len() > MAX_SIDE as usize {
+            return Err(LevelError::TooLarge { width: expected, height: rows.len() });
+        }
+        let mut obstacles = Vec::new();
+        let mut snake = None;
+        for (row, line) in rows.iter().enumerate() {
+            let width = line.chars().count();
+            if width != expected {
+                return Err(LevelError::RaggedRow { row, width, expected });
+            }
+            for (col, tile) in line.chars().enumerate() {
+                let pos = (col as i32, row as i32);
+                let heading = match tile {
+                    '#' => {
+                        obstacles.push(pos);
+                        continue;
+                    }
+                    '.' | ' ' => continue,
+                    '^' => Direction::Up,
+                    'v' => Direction::Down,
+                    '<' => Direction::Left,
+                    '>' => Direction::Right,
+                    tile => return Err(LevelError::UnknownTile { row, col, tile }),
+                };
+                if snake.is_some() {
+                    return Err(LevelError::MultipleSnakes { row, col });
+                }
+                snake = Some((pos, heading));
+            }
+        }
+        let Some((start, heading)) = snake else {
+            return Err(LevelError::MissingSnake);
+        };
+
+        let level = Level {
+            name: name.to_string(),
+            size: (expected as i32, rows.len() as i32),
+            obstacles,
+            start,
+            heading,
+        };
+        //The starting body must fit on the board without touching an obstacle
+        let fits = level.start_body().iter().all(|&(x, y)| {
+            (0..level.size.0).contains(&x)
+                && (0..level.size.1).contains(&y)
+                && !level.obstacles.contains(&(x, y))
+        });
+        if !fits {
+            return Err(LevelError::SnakeBlocked);
+        }
+        Ok(level)
+    }
+
+    //The cells of the starting snake, head first
+    pub fn start_body(&self) -> Vec<(i32, i32)> {
+        let (dx, dy) = self.heading.delta();
+        (0..START_LENGTH)
+            .map(|i| (self.start.0 - dx * i, self.start.1 - dy * i))
+            .collect()
+    }
+
+    //Getters
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+    pub fn get_size(&self) -> (i32, i32) {
+        self.size
+    }
+    pub fn get_obstacles(&self) -> &[(i32, i32)] {
+        &self.obstacles
+    }
+    pub fn get_start(&self) -> (i32, i32) {
+        self.start
+    }
+    pub fn get_heading(&self) -> Direction {
+        self.heading.clone()
+    }
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LevelError::Io(err) => write!(f, "could not read level: {err}"),
+            LevelError::Empty => write!(f, "level is empty"),
+            LevelError::RaggedRow { row, width, expected } => write!(
+                f,
+                "row {} is {width} tiles wide, expected {expected}",
+                row + 1
+            ),
+            LevelError::UnknownTile { row, col, tile } => {
+                write!(f, "unknown tile '{tile}' at row {}, column {}", row + 1, col + 1)
+            }
+            LevelError::MissingSnake => write!(f, "level has no snake start (^, v, < or >)"),
+            LevelError::MultipleSnakes { row, col } => write!(
+                f,
+                "second snake start at row {}, column {}",
+                row + 1,
+                col + 1
+            ),
+            LevelError::SnakeBlocked => write!(
+                f,
+                "the starting snake ({START_LENGTH} long, behind its head) leaves the board or hits an obstacle"
+            ),
+            LevelError::TooLarge { width, height } => write!(
+                f,
+                "level is {width}x{height} tiles, it can be at most {MAX_SIDE}x{MAX_SIDE}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LevelError {}
@@ -1,89 +1,133 @@
-mod render;
+pub mod agent;
+pub mod autopilot;
+pub mod bindings;
+pub mod board;
+pub mod clock;
+pub mod export;
+pub mod external;
+pub mod framebuffer;
+pub mod hamilton;
+pub mod highscore;
+pub mod level;
+pub mod options;
+pub mod render;
+pub mod replay;
+pub mod save;
+pub mod settings;
 pub mod snake;
+pub mod speed;
+pub mod terminal;
+pub mod tournament;
+mod window;
 
-use render::{draw_block, draw_text, to_gui_coord_u32};
-use snake::{Block, Collision, Direction, Snake};
+pub use window::{play_game, play_replay};
 
-use piston_window::{
-    clear, types::Color, Button, Context, G2d, Key, PistonWindow, PressEvent, UpdateEvent,
-    WindowSettings, Glyphs,
-};
-use rand::{Rng, rngs::ThreadRng};
-use std::collections::LinkedList;
+use bindings::Action;
+use board::Board;
+use clock::Clock;
+use level::Level;
+use snake::{Collision, Direction, EdgePolicy, Snake};
+use speed::SpeedCurve;
 
-const APPLE_COLOUR: Color = [0.95, 0.30, 0.1, 1.0];
-const SNAKE_COLOUR: Color = [0.18, 0.80, 0.44, 1.0];
-const BG_COLOUR: Color = [0.204, 0.286, 0.369, 1.0];
-const GAMEOVER_COLOR: Color = [0.91, 0.30, 0.24, 0.5];
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 
-const STEP_TIME: f64 = 0.2; // in second
-#[derive(Debug, Clone)]
+//How many turns can be buffered between two ticks
+const MAX_QUEUED_TURNS: usize = 3;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "save::SavedGame")]
 pub struct Game {
     game_size: (i32, i32),
 
     snake: Snake,
+    start_length: i32,
+    #[serde(skip)]
+    board: Board,
+    edge_policy: EdgePolicy,
+    level: Option<Level>,
     state: GameState,
-    interval: f64,
+    queued_turns: VecDeque<Direction>,
+    clock: Clock,
+    speed_curve: SpeedCurve,
+    ticks: u64,
+    elapsed: f64,
     apple_loc: (i32, i32),
     points: i32,
+    death_cause: Option<Collision>,
+    seed: u64,
+    rng: ChaCha8Rng,
 }
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum GameState {
     Waiting,
     Moving(Direction),
+    Paused(Direction),
     AteApple,
     Dead,
-}
-
-pub fn play_game(width: i32, height: i32) {
-    let window_settings =
-        WindowSettings::new("Snake", [to_gui_coord_u32(width), to_gui_coord_u32(height)])
-            .resizable(false)    
-            .exit_on_esc(true);
-
-    //Create window
-    let mut window: PistonWindow = window_settings.build().unwrap();
-
-    let assets = find_folder::Search::ParentsThenKids(3, 3)
-        .for_folder("assets")
-        .unwrap();
-    let ref font = assets.join("retro-gaming.ttf");
-    let mut glyphs: Glyphs = window.load_font(font).unwrap();
-
-    let mut game = Game::new(width, height);
-
-    while let Some(event) = window.next() {
-        if let Some(Button::Keyboard(key)) = event.press_args() {
-            //Handle keypress
-            game.handle_keypress(key);
-        }
-
-        // Draw game
-        window.draw_2d(&event, |ctx, g, _| {
-            clear(BG_COLOUR, g);
-            game.draw(&ctx, g, &mut glyphs);
-        });
-
-        // Update the state of the game
-        event.update(|arg| {
-            game.update(arg.dt);
-        });
-    }
+    Won,
 }
 
 impl Game {
     //Construction
-    fn new(width: i32, height: i32) -> Self {
-        let snake = Snake::init_snake(3, Direction::Right, (width, height));
-        let apple_loc = Game::generate_random_apple_location((width, height), snake.get_body());
+    pub fn new(width: i32, height: i32, seed: u64) -> Self {
+        Game::with_length(width, height, level::START_LENGTH, seed)
+    }
+    //The snake starts in the middle, so `length` must be at most width / 2 + 1
+    pub fn with_length(width: i32, height: i32, length: i32, seed: u64) -> Self {
+        let snake = Snake::init_snake(length, Direction::Right, (width, height));
+        let board = Board::from_body((width, height), snake.get_body());
+        Game::with_fresh_apple((width, height), snake, board, None, seed)
+    }
+    pub fn from_level(level: &Level, seed: u64) -> Self {
+        let game_size = level.get_size();
+        let snake = Snake::from_body(&level.start_body(), level.get_heading(), game_size);
+        let board = Game::build_board(game_size, &snake, Some(level));
+        Game::with_fresh_apple(game_size, snake, board, Some(level.clone()), seed)
+    }
+    //The snake and the obstacles of the level, if there is one
+    fn build_board(game_size: (i32, i32), snake: &Snake, level: Option<&Level>) -> Board {
+        let mut board = Board::from_body(game_size, snake.get_body());
+        for &obstacle in level.map_or(&[][..], |level| level.get_obstacles()) {
+            board.add_obstacle(obstacle);
+        }
+        board
+    }
+    fn with_fresh_apple(
+        game_size: (i32, i32),
+        snake: Snake,
+        board: Board,
+        level: Option<Level>,
+        seed: u64,
+    ) -> Self {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let apple = Game::generate_random_apple_location(&board, &mut rng);
+        //A snake that already fills the board has nothing left to eat
+        let (apple_loc, state) = match apple {
+            Some(apple_loc) => (apple_loc, GameState::Waiting),
+            None => (snake.get_head_pos(), GameState::Won),
+        };
         Game {
-            game_size: (width, height),
+            game_size,
             apple_loc,
 
+            start_length: snake.get_length() as i32,
             snake,
-            state: GameState::Waiting,
-            interval: 0.0,
+            board,
+            edge_policy: EdgePolicy::Wrap,
+            level,
+            state,
+            queued_turns: VecDeque::new(),
+            clock: Clock::default(),
+            speed_curve: SpeedCurve::default(),
+            ticks: 0,
+            elapsed: 0.0,
             points: 0,
+            death_cause: None,
+            seed,
+            rng,
         }
     }
     pub fn new_constructed(
@@ -93,120 +137,246 @@ impl Game {
         interval: f64,
         apple_loc: (i32, i32),
         points: i32,
+        seed: u64,
     ) -> Self {
+        let board = Board::from_body(game_size, snake.get_body());
         Game {
             game_size,
+            //A constructed game is a snapshot mid-game, restarts use the usual length
+            start_length: level::START_LENGTH,
             snake,
+            board,
+            edge_policy: EdgePolicy::Wrap,
+            level: None,
             state,
-            interval,
+            queued_turns: VecDeque::new(),
+            clock: Clock::new(clock::STEP_TIME, interval),
+            speed_curve: SpeedCurve::Fixed(clock::STEP_TIME),
+            ticks: 0,
+            elapsed: 0.0,
             apple_loc,
             points,
+            death_cause: None,
+            seed,
+            rng: ChaCha8Rng::seed_from_u64(seed),
         }
     }
 
     //Game Logic
+    //Thin real-time driver around step(): only decides *when* a tick happens.
     pub fn update(&mut self, delta_time: f64) {
-        match &self.state {
-            GameState::Waiting => {
-                return;
+        match self.state {
+            GameState::Moving(_) => {
+                self.elapsed += delta_time;
+                if self.clock.advance(delta_time) {
+                    self.step();
+                }
             }
+            //Growing happens right away instead of waiting for the next tick
+            GameState::AteApple => self.step(),
+            //Paused keeps the clock frozen where it was
+            GameState::Waiting | GameState::Paused(_) | GameState::Dead | GameState::Won => {}
+        }
+    }
+    //Advances the rules by exactly one tick, independent of any frontend or timing
+    pub fn step(&mut self) {
+        match &self.state {
+            GameState::Waiting | GameState::Paused(_) => {}
             GameState::Moving(dir) => {
-                self.interval -= delta_time;
-                if self.interval <= 0.0 {
-                    self.snake.move_snake(dir);
-                    self.snake.prev_dir = dir.clone();
-                    let col = self.snake.check_collision(self.apple_loc);
-                    self.handle_collision(col);
-                    self.interval = STEP_TIME;
+                //Apply at most one buffered turn per tick
+                let dir = self.queued_turns.pop_front().unwrap_or_else(|| dir.clone());
+                self.ticks += 1;
+                if !self.snake.move_snake(&dir, self.edge_policy, &mut self.board) {
+                    self.handle_collision(Collision::Wall);
+                    return;
                 }
+                self.snake.prev_dir = dir.clone();
+                self.state = GameState::Moving(dir);
+                let col = self.snake.check_collision(self.apple_loc, &self.board);
+                self.handle_collision(col);
             }
             GameState::AteApple => {
                 self.points += 1;
-                println!("Points: {:?}", self.points);
-                self.snake.grow_snake();
-                self.apple_loc =
-                    Game::generate_random_apple_location(self.game_size, self.snake.get_body());
-                self.state = GameState::Moving(self.snake.prev_dir.clone());
+                self.clock.set_step_time(self.speed_curve.step_time(self.points));
+                self.snake.grow_snake(&mut self.board);
+                //Once the grown tail unfolds the snake covers every open cell, so the game is won
+                let apple = if self.snake.get_length() >= self.board.open_cells() {
+                    None
+                } else {
+                    Game::generate_random_apple_location(&self.board, &mut self.rng)
+                };
+                match apple {
+                    Some(apple_loc) => {
+                        self.apple_loc = apple_loc;
+                        self.state = GameState::Moving(self.snake.prev_dir.clone());
+                    }
+                    None => self.state = GameState::Won,
+                }
             }
-            GameState::Dead => {
-                todo!("Add death state with restart");
+            GameState::Dead | GameState::Won => {
+                //The board stays frozen until the player restarts
             }
         }
     }
-    pub fn handle_keypress(&mut self, key: Key) {
-        let dir = match key {
-            Key::W | Key::Up => Direction::Up,
-            Key::S | Key::Down => Direction::Down,
-            Key::A | Key::Left => Direction::Left,
-            Key::D | Key::Right => Direction::Right,
-            _ => return,
-        };
-        self.update_move_dir(dir);
+    pub fn pause(&mut self) {
+        if let GameState::Moving(dir) = &self.state {
+            self.state = GameState::Paused(dir.clone());
+        }
     }
-    pub fn update_move_dir(&mut self, dir: Direction) {
-        if self.is_opposite(&dir) {
-            return;
+    pub fn resume(&mut self) {
+        if let GameState::Paused(dir) = &self.state {
+            self.state = GameState::Moving(dir.clone());
         }
+    }
+    pub fn toggle_pause(&mut self) {
         match self.state {
-            GameState::Moving(_) | GameState::Waiting => self.state = GameState::Moving(dir),
-            _ => return,
+            GameState::Moving(_) => self.pause(),
+            GameState::Paused(_) => self.resume(),
+            _ => {}
         }
     }
-    fn generate_random_apple_location(game_size: (i32, i32),snake_body: &LinkedList<Block>,) -> (i32, i32) {
-        let mut rng = rand::thread_rng();
-
-        let (mut x, mut y) = Game::gen_random_location(game_size, &mut rng);
-        //Make sure the apple doesn't intersect with the snake body
-        while Snake::intersects_body(&snake_body, (x, y)) {
-            (x, y) = Game::gen_random_location(game_size, &mut rng);
+    //Applies a bound key; restart only works once the game is over.
+    //Quitting, saving and loading are up to the frontend, the game itself ignores them.
+    pub fn handle_action(&mut self, action: Action) {
+        match action {
+            Action::Restart if self.is_over() => self.restart(),
+            Action::Pause if !self.is_over() => self.toggle_pause(),
+            Action::Move(dir) if !self.is_over() => self.update_move_dir(dir),
+            _ => {}
         }
-        (x, y)
     }
-
-    fn gen_random_location(game_size: (i32, i32), rng: &mut ThreadRng) -> (i32, i32) {
-        (rng.gen_range(1..(game_size.0 - 1)), rng.gen_range(1..(game_size.1 - 1)))
+    pub fn restart(&mut self) {
+        //Derive the next seed from this game's RNG so a whole session stays reproducible
+        let seed = self.rng.gen();
+        *self = self.reseeded(seed);
     }
-
-    //Rendering
-    fn draw(&self, ctx: &Context, g: &mut G2d, glyphs: &mut Glyphs) {
-        for block in self.snake.get_body() {
-            draw_block(SNAKE_COLOUR, block.x, block.y, ctx, g);
-        }
-        draw_block(APPLE_COLOUR, self.apple_loc.0, self.apple_loc.1, ctx, g);
-        if self.state == GameState::Dead{
-            draw_text(&ctx, g, glyphs, GAMEOVER_COLOR, (10.0, 10.0), ":C");
+    //A new game on the same board with the same settings, but apples from another seed
+    pub fn reseeded(&self, seed: u64) -> Game {
+        let mut game = match &self.level {
+            Some(level) => Game::from_level(level, seed),
+            None => Game::with_length(self.game_size.0, self.game_size.1, self.start_length, seed),
+        };
+        game.set_edge_policy(self.edge_policy);
+        game.set_speed_curve(self.speed_curve.clone());
+        game
+    }
+    pub fn update_move_dir(&mut self, dir: Direction) {
+        match self.state {
+            GameState::Waiting => {
+                if self.is_opposite(&dir) {
+                    return;
+                }
+                self.state = GameState::Moving(self.snake.prev_dir.clone());
+                self.queue_turn(dir);
+            }
+            GameState::Moving(_) | GameState::AteApple => self.queue_turn(dir),
+            _ => {}
         }
-        // draw_text(&ctx, g, glyphs, GAMEOVER_COLOR, (1.0, 2.0), &self.points.to_string().clone()); 
+    }
+    //Picks uniformly among the cells not covered by the snake, or None when the board is full
+    fn generate_random_apple_location(board: &Board, rng: &mut ChaCha8Rng) -> Option<(i32, i32)> {
+        board.random_free_cell(rng)
     }
 
     //private functions
     fn is_opposite(&self, dir: &Direction) -> bool {
         self.snake.prev_dir.opposite() == *dir
     }
+    //The direction the snake will have once every queued turn has been applied
+    fn planned_dir(&self) -> Direction {
+        match (self.queued_turns.back(), &self.state) {
+            (Some(dir), _) => dir.clone(),
+            (None, GameState::Moving(dir)) => dir.clone(),
+            (None, _) => self.snake.prev_dir.clone(),
+        }
+    }
+    fn queue_turn(&mut self, dir: Direction) {
+        let planned = self.planned_dir();
+        if dir == planned || dir == planned.opposite() {
+            return;
+        }
+        if self.queued_turns.len() < MAX_QUEUED_TURNS {
+            self.queued_turns.push_back(dir);
+        }
+    }
     fn handle_collision(&mut self, col: Collision) {
         match col {
             Collision::Apple => {
                 self.state = GameState::AteApple;
             }
-            Collision::Snake => self.state = GameState::Dead,
+            Collision::Snake | Collision::Wall | Collision::Obstacle => {
+                self.state = GameState::Dead;
+                self.death_cause = Some(col);
+            }
             Collision::None => {}
         }
     }
 
+    //Dead or won: nothing moves until the player restarts
+    pub fn is_over(&self) -> bool {
+        matches!(self.state, GameState::Dead | GameState::Won)
+    }
+
+    //Setters
+    pub fn set_edge_policy(&mut self, edge_policy: EdgePolicy) {
+        self.edge_policy = edge_policy;
+    }
+    pub fn set_speed_curve(&mut self, speed_curve: SpeedCurve) {
+        self.clock.set_step_time(speed_curve.step_time(self.points));
+        self.speed_curve = speed_curve;
+    }
+
     //Getters
     pub fn get_game_size(&self) -> (i32, i32) {
-        self.game_size.clone()
+        self.game_size
+    }
+    pub fn get_start_length(&self) -> i32 {
+        self.start_length
     }
     pub fn get_snake(&self) -> Snake {
         self.snake.clone()
     }
+    pub fn get_board(&self) -> &Board {
+        &self.board
+    }
+    pub fn get_edge_policy(&self) -> EdgePolicy {
+        self.edge_policy
+    }
+    pub fn get_level(&self) -> Option<&Level> {
+        self.level.as_ref()
+    }
     pub fn get_state(&self) -> GameState {
         self.state.clone()
     }
     pub fn get_apple_loc(&self) -> (i32, i32) {
-        self.apple_loc.clone()
+        self.apple_loc
     }
     pub fn get_points(&self) -> i32 {
         self.points
     }
+    pub fn get_death_cause(&self) -> Option<Collision> {
+        self.death_cause.clone()
+    }
+    pub fn get_seed(&self) -> u64 {
+        self.seed
+    }
+    pub fn get_queued_turns(&self) -> &VecDeque<Direction> {
+        &self.queued_turns
+    }
+    pub fn get_ticks(&self) -> u64 {
+        self.ticks
+    }
+    pub fn get_clock(&self) -> &Clock {
+        &self.clock
+    }
+    //Seconds spent moving, i.e. not counting waiting for the first key or the game-over screen
+    pub fn get_elapsed(&self) -> f64 {
+        self.elapsed
+    }
+    pub fn get_speed_curve(&self) -> &SpeedCurve {
+        &self.speed_curve
+    }
+    pub fn get_ticks_per_second(&self) -> f64 {
+        1.0 / self.clock.get_step_time()
+    }
 }
\ No newline at end of file
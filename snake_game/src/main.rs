@@ -1,4 +1,58 @@
-use snake_game::play_game;
+use std::error::Error;
+
+use clap::Parser;
+
+use snake_game::export::export_gif;
+use snake_game::options::Options;
+use snake_game::replay::Replay;
+use snake_game::terminal::{play_replay_terminal, play_terminal};
+use snake_game::{play_game, play_replay};
+
 fn main() {
-    play_game(20,20);
-}
\ No newline at end of file
+    //e.g. `cargo run -- --width 30 --height 20 --edges solid` or `cargo run -- --level levels/box.txt`
+    let options = Options::parse();
+    if let Err(err) = run(&options) {
+        eprintln!("error: {err}");
+        std::process::exit(2);
+    }
+}
+
+fn run(options: &Options) -> Result<(), Box<dyn Error>> {
+    let settings = options.load_settings()?;
+    match options.load_replay()? {
+        Some(replay) if options.verify => verify(&replay),
+        Some(replay) => match &options.gif {
+            Some(path) => {
+                let frames = export_gif(&replay, &settings.colours, path)?;
+                println!("wrote {frames} frames to {}", path.display());
+            }
+            None if options.terminal => play_replay_terminal(replay, &settings)?,
+            None => play_replay(replay, &settings, options.scale),
+        },
+        None => {
+            let game = options.build_game(&settings)?;
+            let autopilot = options.build_autopilot(&game)?;
+            if options.terminal {
+                play_terminal(game, &settings, autopilot)?
+            } else {
+                play_game(game, &settings, options.scale, autopilot)
+            }
+        }
+    }
+    Ok(())
+}
+
+//Headless check that a replay still plays out the same, e.g. after changing the rules
+fn verify(replay: &Replay) {
+    match replay.verify() {
+        Ok(_) => println!(
+            "replay ok: {} points after {} ticks",
+            replay.get_points(),
+            replay.get_ticks()
+        ),
+        Err(err) => {
+            println!("replay failed: {err}");
+            std::process::exit(1);
+        }
+    }
+}
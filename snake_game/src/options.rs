@@ -0,0 +1,244 @@
+use std::fmt;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use clap::Parser;
+
+use crate::autopilot::{Autopilot, AutopilotMode};
+use crate::board::MAX_SIDE;
+use crate::external::{BotError, ExternalBot, DEFAULT_TIMEOUT};
+use crate::level::{Level, LevelError};
+use crate::replay::{Replay, ReplayError};
+use crate::save::SaveError;
+use crate::settings::{Settings, SettingsError};
+use crate::snake::EdgePolicy;
+use crate::Game;
+
+//Smallest and largest window scale
+const MIN_SCALE: f64 = 0.25;
+const MAX_SCALE: f64 = 4.0;
+
+//Command-line options of the snake_game binary
+#[derive(Debug, Clone, Parser)]
+#[command(name = "snake_game", about = "A snake game")]
+pub struct Options {
+    /// Board width in cells
+    #[arg(long, default_value_t = 20, conflicts_with = "level")]
+    pub width: i32,
+    /// Board height in cells
+    #[arg(long, default_value_t = 20, conflicts_with = "level")]
+    pub height: i32,
+    /// Starting length of the snake
+    #[arg(long, default_value_t = 3, conflicts_with = "level")]
+    pub length: i32,
+    /// Starting speed in ticks per second, defaults to the step time from the settings
+    #[arg(long)]
+    pub speed: Option<f64>,
+    /// Keep the starting speed instead of speeding up with the points
+    #[arg(long)]
+    pub fixed_speed: bool,
+    /// What happens at the edge of the board: wrap or solid
+    #[arg(long, default_value_t = EdgePolicy::Wrap)]
+    pub edges: EdgePolicy,
+    /// Seed for the apple placement, random if not given
+    #[arg(long)]
+    pub seed: Option<u64>,
+    /// Level file to play instead of an empty board
+    #[arg(long)]
+    pub level: Option<PathBuf>,
+    /// Window scale, multiplies the block size from the settings
+    #[arg(long, default_value_t = 1.0)]
+    pub scale: f64,
+    /// Saved game to continue, see the save and load keys
+    #[arg(long, conflicts_with_all = ["level", "width", "height", "length", "seed", "edges", "speed", "fixed_speed"])]
+    pub load: Option<PathBuf>,
+    /// Replay to watch, e.g. last_replay.json from the data directory
+    #[arg(long, conflicts_with_all = ["level", "width", "height", "length", "seed", "edges", "speed", "fixed_speed", "load"])]
+    pub replay: Option<PathBuf>,
+    /// Check the replay without opening a window: play it and compare the final score
+    #[arg(long, requires = "replay")]
+    pub verify: bool,
+    /// Render the replay into an animated GIF without opening a window
+    #[arg(long, value_name = "FILE", requires = "replay", conflicts_with_all = ["verify", "terminal"])]
+    pub gif: Option<PathBuf>,
+    /// Settings file with key bindings, colours and more, defaults to settings.toml in the config directory
+    #[arg(long)]
+    pub settings: Option<PathBuf>,
+    /// Play in the terminal instead of a window, e.g. over SSH
+    #[arg(long, conflicts_with = "scale")]
+    pub terminal: bool,
+    /// Let the game play itself: path heads for the apple, cycle follows a route that fills the board
+    #[arg(long, value_name = "BOT", num_args = 0..=1, default_missing_value = "path", conflicts_with = "replay")]
+    pub autopilot: Option<AutopilotMode>,
+    /// Let a bot in another program play, e.g. "python3 bots/greedy.py", see the readme for the protocol
+    #[arg(long, value_name = "COMMAND", conflicts_with_all = ["replay", "autopilot"])]
+    pub bot: Option<String>,
+    /// Milliseconds the bot may take for a move before the snake goes straight on
+    #[arg(long, value_name = "MS", default_value_t = DEFAULT_TIMEOUT.as_millis() as u64, requires = "bot",
+          value_parser = clap::value_parser!(u64).range(1..))]
+    pub bot_timeout: u64,
+    /// File to log everything sent to and received from the bot
+    #[arg(long, value_name = "FILE", requires = "bot")]
+    pub bot_log: Option<PathBuf>,
+}
+
+#[derive(Debug)]
+pub enum OptionsError {
+    BoardTooSmall { width: i32, height: i32 },
+    BoardTooLarge { width: i32, height: i32 },
+    InvalidLength { length: i32 },
+    SnakeTooLong { length: i32, max: i32 },
+    InvalidSpeed { speed: f64 },
+    InvalidScale { scale: f64 },
+    Level { path: PathBuf, error: LevelError },
+    Settings { path: PathBuf, error: SettingsError },
+    Save { path: PathBuf, error: SaveError },
+    Replay { path: PathBuf, error: ReplayError },
+    NoCycle { width: i32, height: i32 },
+    Bot { command: String, error: BotError },
+}
+
+impl Options {
+    //Checks the options and builds the game they describe, options not given come from the settings
+    pub fn build_game(&self, settings: &Settings) -> Result<Game, OptionsError> {
+        let step_time = match self.speed {
+            Some(speed) if speed.is_finite() && speed > 0.0 => 1.0 / speed,
+            Some(speed) => return Err(OptionsError::InvalidSpeed { speed }),
+            None => settings.speed.step_time,
+        };
+        if !(MIN_SCALE..=MAX_SCALE).contains(&self.scale) {
+            return Err(OptionsError::InvalidScale { scale: self.scale });
+        }
+        if let Some(path) = &self.load {
+            let mut game = Game::load(path).map_err(|error| OptionsError::Save {
+                path: path.clone(),
+                error,
+            })?;
+            game.pause();
+            return Ok(game);
+        }
+        let seed = self.seed.unwrap_or_else(rand::random);
+
+        let mut game = match &self.level {
+            Some(path) => {
+                let level = Level::load(path).map_err(|error| OptionsError::Level {
+                    path: path.clone(),
+                    error,
+                })?;
+                Game::from_level(&level, seed)
+            }
+            None => {
+                self.check_board()?;
+                Game::with_length(self.width, self.height, self.length, seed)
+            }
+        };
+        game.set_edge_policy(self.edges);
+        game.set_speed_curve(settings.speed.curve(step_time, self.fixed_speed));
+        Ok(game)
+    }
+
+    //The bot given with --autopilot or --bot for `game`, if any
+    pub fn build_autopilot(&self, game: &Game) -> Result<Option<Autopilot>, OptionsError> {
+        if let Some(command) = &self.bot {
+            let timeout = Duration::from_millis(self.bot_timeout);
+            let bot = ExternalBot::spawn(command, timeout, self.bot_log.as_deref()).map_err(|error| {
+                OptionsError::Bot {
+                    command: command.clone(),
+                    error,
+                }
+            })?;
+            return Ok(Some(Autopilot::with_agent(Box::new(bot))));
+        }
+        let Some(mode) = self.autopilot else {
+            return Ok(None);
+        };
+        let (width, height) = game.get_game_size();
+        Autopilot::for_mode(mode, game)
+            .map(Some)
+            .ok_or(OptionsError::NoCycle { width, height })
+    }
+
+    //The replay given with --replay, if any
+    pub fn load_replay(&self) -> Result<Option<Replay>, OptionsError> {
+        let Some(path) = &self.replay else {
+            return Ok(None);
+        };
+        let replay = Replay::load(path).map_err(|error| OptionsError::Replay {
+            path: path.clone(),
+            error,
+        })?;
+        Ok(Some(replay))
+    }
+    //The file given with --settings, or the default settings file if there is one
+    pub fn load_settings(&self) -> Result<Settings, OptionsError> {
+        let Some(path) = self.settings.clone().or_else(Settings::default_path) else {
+            return Ok(Settings::default());
+        };
+        Settings::load(&path).map_err(|error| OptionsError::Settings { path, error })
+    }
+
+    //private functions
+    fn check_board(&self) -> Result<(), OptionsError> {
+        check_board(self.width, self.height, self.length)
+    }
+}
+
+//Checks that a snake of `length` fits on a `width` by `height` board
+pub(crate) fn check_board(width: i32, height: i32, length: i32) -> Result<(), OptionsError> {
+    if width < 2 || height < 1 {
+        return Err(OptionsError::BoardTooSmall { width, height });
+    }
+    if width > MAX_SIDE || height > MAX_SIDE {
+        return Err(OptionsError::BoardTooLarge { width, height });
+    }
+    if length < 1 {
+        return Err(OptionsError::InvalidLength { length });
+    }
+    //The snake starts with its head in the middle and its body to the left,
+    //and there has to be at least one free cell for the apple
+    let max = (width / 2 + 1).min(width * height - 1);
+    if length > max {
+        return Err(OptionsError::SnakeTooLong { length, max });
+    }
+    Ok(())
+}
+
+impl fmt::Display for OptionsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OptionsError::BoardTooSmall { width, height } => write!(
+                f,
+                "a {width}x{height} board is too small, it needs to be at least 2x1"
+            ),
+            OptionsError::BoardTooLarge { width, height } => write!(
+                f,
+                "a {width}x{height} board is too large, it can be at most {MAX_SIDE}x{MAX_SIDE}"
+            ),
+            OptionsError::InvalidLength { length } => {
+                write!(f, "the snake length must be at least 1, got {length}")
+            }
+            OptionsError::SnakeTooLong { length, max } => write!(
+                f,
+                "a snake of length {length} does not fit on this board, the maximum is {max}"
+            ),
+            OptionsError::InvalidSpeed { speed } => {
+                write!(f, "the speed must be a positive number of ticks per second, got {speed}")
+            }
+            OptionsError::InvalidScale { scale } => {
+                write!(f, "the scale must be between {MIN_SCALE} and {MAX_SCALE}, got {scale}")
+            }
+            OptionsError::Level { path, error } => write!(f, "{}: {error}", path.display()),
+            OptionsError::Settings { path, error } => write!(f, "{}: {error}", path.display()),
+            OptionsError::Save { path, error } => write!(f, "{}: {error}", path.display()),
+            OptionsError::Replay { path, error } => write!(f, "{}: {error}", path.display()),
+            OptionsError::NoCycle { width, height } => write!(
+                f,
+                "the cycle autopilot cannot fill this {width}x{height} board, it needs a board \
+                 without obstacles and an even number of cells or wrapping edges"
+            ),
+            OptionsError::Bot { command, error } => write!(f, "{command}: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for OptionsError {}
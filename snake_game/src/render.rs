@@ -1,9 +1,64 @@
-use piston_window::{text, rectangle, Context, G2d, Glyphs, Transformed};
-use piston_window::types::Color;
+use std::io;
 
-const BLOCK_SIZE: f64 = 25.0;
+use piston_window::Key;
 
-fn to_gui_coord(game_coord: i32) -> f64 {
+use crate::settings::Colours;
+use crate::snake::EdgePolicy;
+use crate::{Game, GameState};
+
+//RGBA with every channel from 0 to 1, the same layout as piston's colours
+pub type Color = [f32; 4];
+
+//Drawing unit, the window scales it to the block size from the settings
+pub const BLOCK_SIZE: f64 = 25.0;
+
+//Rows above the playfield reserved for the HUD
+pub const HUD_ROWS: i32 = 2;
+
+//What a block shows, for backends that draw more than a coloured square (e.g. a terminal)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cell {
+    //A plain area of colour, e.g. the HUD background or the pause overlay
+    Fill,
+    Snake,
+    Apple,
+    Obstacle,
+    Wall,
+}
+
+//A drawing backend. Positions are in blocks from the top-left corner of the screen,
+//text sizes are in pixels at BLOCK_SIZE and colours may be translucent.
+pub trait Renderer {
+    fn clear(&mut self, colour: Color);
+    fn draw_cell(&mut self, cell: Cell, colour: Color, x: i32, y: i32);
+    //`pos` is the left end of the text's baseline
+    fn draw_text(&mut self, colour: Color, size: u32, pos: (f64, f64), text: &str);
+    //Shows the finished frame, backends that show it on their own do nothing
+    fn present(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+//Solid edges get a one block wall drawn around the playfield
+pub fn border_width(edge_policy: EdgePolicy) -> i32 {
+    match edge_policy {
+        EdgePolicy::Wrap => 0,
+        EdgePolicy::Solid => 1,
+    }
+}
+
+//Screen size in blocks for a board with the HUD on top and an optional border around it
+pub fn screen_size(game_size: (i32, i32), border: i32) -> (i32, i32) {
+    (game_size.0 + 2 * border, game_size.1 + 2 * border + HUD_ROWS)
+}
+
+//Screen size in pixels at BLOCK_SIZE
+pub(crate) fn window_size(game_size: (i32, i32), border: i32) -> [u32; 2] {
+    let (width, height) = screen_size(game_size, border);
+    [to_gui_coord_u32(width), to_gui_coord_u32(height)]
+}
+
+pub(crate) fn to_gui_coord(game_coord: i32) -> f64 {
     (game_coord as f64) * BLOCK_SIZE
 }
 
@@ -11,21 +66,94 @@ pub(crate) fn to_gui_coord_u32(game_coord: i32) -> u32 {
     to_gui_coord(game_coord) as u32
 }
 
-pub(crate) fn draw_block(color: Color, x: i32, y: i32, con: &Context, g: &mut G2d) {
-    let gui_x = to_gui_coord(x);
-    let gui_y = to_gui_coord(y);
-
-    rectangle(color, [gui_x, gui_y, BLOCK_SIZE, BLOCK_SIZE], con.transform,g);
+//e.g. "Press R to restart", using the first key bound to the action
+pub(crate) fn press_hint(keys: &[Key], what: &str) -> String {
+    match keys.first() {
+        Some(key) => format!("Press {key:?} to {what}"),
+        None => format!("No key to {what}"),
+    }
 }
 
-pub fn draw_text(ctx: &Context,graphics: &mut G2d, glyphs: &mut Glyphs, color: Color, pos: (f64,f64),text: &str,) {
-    text::Text::new_color(color, 50)
-        .draw(
-            text,
-            glyphs,
-            &ctx.draw_state,
-            ctx.transform.trans(pos.0 *BLOCK_SIZE, pos.1*BLOCK_SIZE),
-            graphics,
-        )
-        .unwrap();
-}
\ No newline at end of file
+impl Game {
+    //Rendering
+    //Draws the HUD, the board and any message on top of it, the frontend presents the frame
+    pub fn draw(&self, r: &mut impl Renderer, colours: &Colours, resume_hint: &str) {
+        r.clear(colours.background);
+        self.draw_hud(r, colours);
+        let border = border_width(self.edge_policy);
+        let (left, top) = (border, border + HUD_ROWS);
+        if self.edge_policy == EdgePolicy::Solid {
+            self.draw_walls(r, colours, (left, top));
+        }
+        for &(x, y) in self.board.get_obstacles() {
+            r.draw_cell(Cell::Obstacle, colours.obstacle, x + left, y + top);
+        }
+        for block in self.snake.get_body() {
+            r.draw_cell(Cell::Snake, colours.snake, block.x + left, block.y + top);
+        }
+        //Once won, the "apple" sits under the snake and there is nothing left to eat
+        if self.state != GameState::Won {
+            let (x, y) = self.apple_loc;
+            r.draw_cell(Cell::Apple, colours.apple, x + left, y + top);
+        }
+        let (title, colour) = match self.state {
+            GameState::Dead => ("Game over", colours.game_over),
+            GameState::Won => ("You win!", colours.won),
+            GameState::Paused(_) => {
+                self.draw_pause_overlay(r, colours, resume_hint);
+                return;
+            }
+            _ => return,
+        };
+        let score = format!("Score: {}", self.points);
+        let top = HUD_ROWS as f64;
+        r.draw_text(colour, 50, (1.0, top + 3.0), title);
+        r.draw_text(colour, 50, (1.0, top + 6.0), &score);
+    }
+
+    //private functions
+    //Dims the whole playfield and tells the player how to continue
+    fn draw_pause_overlay(&self, r: &mut impl Renderer, colours: &Colours, hint: &str) {
+        let (width, height) = screen_size(self.game_size, border_width(self.edge_policy));
+        for x in 0..width {
+            for y in HUD_ROWS..height {
+                r.draw_cell(Cell::Fill, colours.pause_overlay, x, y);
+            }
+        }
+        let top = HUD_ROWS as f64;
+        r.draw_text(colours.pause_text, 50, (1.0, top + 3.0), "Paused");
+        r.draw_text(colours.pause_text, 20, (1.0, top + 5.0), hint);
+    }
+    //Points, length, elapsed time and tick speed in the rows above the playfield
+    fn draw_hud(&self, r: &mut impl Renderer, colours: &Colours) {
+        let width = self.game_size.0 + 2 * border_width(self.edge_policy);
+        for x in 0..width {
+            for y in 0..HUD_ROWS {
+                r.draw_cell(Cell::Fill, colours.hud_background, x, y);
+            }
+        }
+        let seconds = self.elapsed as u64;
+        let stats = format!("Points: {}   Length: {}", self.points, self.snake.get_length());
+        let timing = format!(
+            "Time: {}:{:02}   Speed: {:.1}/s",
+            seconds / 60,
+            seconds % 60,
+            self.get_ticks_per_second()
+        );
+        r.draw_text(colours.hud_text, 16, (0.4, 0.85), &stats);
+        r.draw_text(colours.hud_text, 16, (0.4, 1.75), &timing);
+    }
+    //`origin` is where the board's 0,0 is drawn
+    fn draw_walls(&self, r: &mut impl Renderer, colours: &Colours, origin: (i32, i32)) {
+        let (width, height) = self.game_size;
+        let (left, top) = origin;
+        for x in -1..=width {
+            r.draw_cell(Cell::Wall, colours.wall, x + left, top - 1);
+            r.draw_cell(Cell::Wall, colours.wall, x + left, height + top);
+        }
+        for y in 0..height {
+            r.draw_cell(Cell::Wall, colours.wall, left - 1, y + top);
+            r.draw_cell(Cell::Wall, colours.wall, width + left, y + top);
+        }
+    }
+}
@@ -0,0 +1,323 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::bindings::Action;
+use crate::board::MAX_SIDE;
+use crate::level::Level;
+use crate::options::check_board;
+use crate::snake::{Direction, EdgePolicy};
+use crate::speed::SpeedCurve;
+use crate::{Game, GameState};
+
+//Version of the replay format, bump it whenever the rules or the fields below change
+pub const REPLAY_VERSION: u32 = 1;
+
+//Everything needed to play a game again: how it was set up and which direction keys
+//were pressed at which tick. The game is deterministic, so this reproduces it exactly.
+//Stored as JSON, with the inputs packed into one string like "0R 14U 20L".
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Replay {
+    version: u32,
+    seed: u64,
+    size: (i32, i32),
+    length: i32,
+    edges: EdgePolicy,
+    speed_curve: SpeedCurve,
+    level: Option<Level>,
+    #[serde(with = "compact_inputs")]
+    inputs: Vec<(u64, Direction)>,
+    //How the game ended, filled in by finish()
+    points: i32,
+    ticks: u64,
+}
+
+#[derive(Deserialize)]
+struct ReplayHeader {
+    version: u32,
+}
+
+//Logs the direction changes of a game while it is played
+#[derive(Debug, Clone)]
+pub struct Recorder {
+    replay: Option<Replay>,
+    finished: bool,
+}
+
+//Feeds the recorded inputs into a game at the ticks they were made
+#[derive(Debug, Clone)]
+pub struct Player {
+    replay: Replay,
+    next: usize,
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+    Format(serde_json::Error),
+    UnsupportedVersion { found: u32 },
+    Mismatch { points: i32, ticks: u64, expected_points: i32, expected_ticks: u64 },
+    //Parses, but cannot be played, e.g. a snake longer than the board
+    Corrupt(String),
+}
+
+impl Replay {
+    //Construction
+    //Only a game that has not started yet can be recorded from the beginning
+    pub fn new(game: &Game) -> Option<Replay> {
+        if game.get_ticks() > 0 || game.get_state() != GameState::Waiting {
+            return None;
+        }
+        Some(Replay {
+            version: REPLAY_VERSION,
+            seed: game.get_seed(),
+            size: game.get_game_size(),
+            length: game.get_start_length(),
+            edges: game.get_edge_policy(),
+            speed_curve: game.get_speed_curve().clone(),
+            level: game.get_level().cloned(),
+            inputs: Vec::new(),
+            points: 0,
+            ticks: 0,
+        })
+    }
+    //The game as it was before the first key press
+    pub fn start_game(&self) -> Game {
+        let mut game = match &self.level {
+            Some(level) => Game::from_level(level, self.seed),
+            None => Game::with_length(self.size.0, self.size.1, self.length, self.seed),
+        };
+        game.set_edge_policy(self.edges);
+        game.set_speed_curve(self.speed_curve.clone());
+        game
+    }
+
+    //Recording
+    pub fn record(&mut self, tick: u64, dir: Direction) {
+        self.inputs.push((tick, dir));
+    }
+    pub fn finish(&mut self, game: &Game) {
+        self.points = game.get_points();
+        self.ticks = game.get_ticks();
+    }
+
+    //Plays the replay through Game::update, one step time per call, and checks
+    //that it ends with the recorded points and ticks
+    pub fn verify(&self) -> Result<Game, ReplayError> {
+        let mut player = Player::new(self.clone());
+        let mut game = self.start_game();
+        while !game.is_over() && game.get_ticks() <= self.ticks {
+            player.feed(&mut game);
+            //Nothing moves the snake any more
+            if game.get_state() == GameState::Waiting {
+                break;
+            }
+            game.update(game.get_clock().get_interval());
+        }
+        if game.get_points() != self.points || game.get_ticks() != self.ticks {
+            return Err(ReplayError::Mismatch {
+                points: game.get_points(),
+                ticks: game.get_ticks(),
+                expected_points: self.points,
+                expected_ticks: self.ticks,
+            });
+        }
+        Ok(game)
+    }
+
+    //Files
+    pub fn save(&self, path: &Path) -> Result<(), ReplayError> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(ReplayError::Io)?;
+        }
+        let text = serde_json::to_string(self).map_err(ReplayError::Format)?;
+        fs::write(path, text).map_err(ReplayError::Io)
+    }
+    pub fn load(path: &Path) -> Result<Replay, ReplayError> {
+        let text = fs::read_to_string(path).map_err(ReplayError::Io)?;
+        Replay::parse(&text)
+    }
+    pub fn parse(text: &str) -> Result<Replay, ReplayError> {
+        //Check the version first, so an old file gives a clear error instead of a missing field
+        let header: ReplayHeader = serde_json::from_str(text).map_err(ReplayError::Format)?;
+        if header.version != REPLAY_VERSION {
+            return Err(ReplayError::UnsupportedVersion { found: header.version });
+        }
+        let replay: Replay = serde_json::from_str(text).map_err(ReplayError::Format)?;
+        replay.check().map_err(ReplayError::Corrupt)?;
+        Ok(replay)
+    }
+    //<data dir>/snake_game/last_replay.json, overwritten after every game
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("snake_game").join("last_replay.json"))
+    }
+    //Saves a finished game to default_path() for the frontends, Err is the message to show
+    pub(crate) fn save_as_last(&self) -> Result<(), String> {
+        let Some(path) = Replay::default_path() else {
+            return Ok(());
+        };
+        self.save(&path)
+            .map_err(|err| format!("Could not save the replay to {}: {err}", path.display()))
+    }
+
+    //Getters
+    pub fn get_inputs(&self) -> &[(u64, Direction)] {
+        &self.inputs
+    }
+    pub fn get_points(&self) -> i32 {
+        self.points
+    }
+    pub fn get_ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    //private functions
+    //Everything start_game() relies on that serde cannot check
+    fn check(&self) -> Result<(), String> {
+        if !self.speed_curve.is_valid() {
+            return Err("the speed curve is broken".to_string());
+        }
+        let Some(level) = &self.level else {
+            return check_board(self.size.0, self.size.1, self.length).map_err(|err| err.to_string());
+        };
+        let (width, height) = self.size;
+        if level.get_size() != self.size {
+            return Err("the level does not match the board size".to_string());
+        }
+        if width > MAX_SIDE || height > MAX_SIDE {
+            return Err("the board is too large".to_string());
+        }
+        let inside = |&(x, y): &(i32, i32)| x >= 0 && y >= 0 && x < width && y < height;
+        if !level.start_body().iter().all(inside) {
+            return Err("the snake starts outside the level".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl Recorder {
+    //Records nothing for a game that is already under way, e.g. a loaded one
+    pub fn new(game: &Game) -> Self {
+        Recorder {
+            replay: Replay::new(game),
+            finished: false,
+        }
+    }
+    //Applies an action to the game, logging direction changes and starting over on restart
+    pub fn apply(&mut self, game: &mut Game, action: Action) {
+        if let (Action::Move(dir), Some(replay)) = (&action, self.replay.as_mut()) {
+            //Paused and finished games ignore direction keys, so there is nothing to replay
+            if let GameState::Waiting | GameState::Moving(_) | GameState::AteApple = game.get_state() {
+                replay.record(game.get_ticks(), dir.clone());
+            }
+        }
+        let restart = action == Action::Restart && game.is_over();
+        game.handle_action(action);
+        if restart {
+            *self = Recorder::new(game);
+        }
+    }
+    //Returns the replay once, the first time the game is seen over
+    pub fn finished(&mut self, game: &Game) -> Option<&Replay> {
+        if self.finished || !game.is_over() {
+            return None;
+        }
+        self.finished = true;
+        let replay = self.replay.as_mut()?;
+        replay.finish(game);
+        Some(replay)
+    }
+    //The replay so far, None if the game is not being recorded
+    pub fn get_replay(&self) -> Option<&Replay> {
+        self.replay.as_ref()
+    }
+}
+
+impl Player {
+    pub fn new(replay: Replay) -> Self {
+        Player { replay, next: 0 }
+    }
+    //Applies every input recorded at the game's current tick
+    pub fn feed(&mut self, game: &mut Game) {
+        while let Some((tick, dir)) = self.replay.inputs.get(self.next) {
+            if *tick > game.get_ticks() {
+                break;
+            }
+            game.update_move_dir(dir.clone());
+            self.next += 1;
+        }
+    }
+    pub fn is_done(&self) -> bool {
+        self.next >= self.replay.inputs.len()
+    }
+    pub fn get_replay(&self) -> &Replay {
+        &self.replay
+    }
+}
+
+//Inputs as "<tick><U|D|L|R>" separated by spaces
+mod compact_inputs {
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    use crate::snake::Direction;
+
+    pub fn serialize<S: Serializer>(inputs: &[(u64, Direction)], s: S) -> Result<S::Ok, S::Error> {
+        let packed: Vec<String> = inputs
+            .iter()
+            .map(|(tick, dir)| {
+                let letter = match dir {
+                    Direction::Up => 'U',
+                    Direction::Down => 'D',
+                    Direction::Left => 'L',
+                    Direction::Right => 'R',
+                };
+                format!("{tick}{letter}")
+            })
+            .collect();
+        s.serialize_str(&packed.join(" "))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<(u64, Direction)>, D::Error> {
+        let packed = String::deserialize(d)?;
+        packed
+            .split_whitespace()
+            .map(|input| {
+                let invalid = || de::Error::custom(format!("invalid input '{input}'"));
+                let letter = input.chars().last().ok_or_else(invalid)?;
+                let tick = &input[..input.len() - letter.len_utf8()];
+                let dir = match letter {
+                    'U' => Direction::Up,
+                    'D' => Direction::Down,
+                    'L' => Direction::Left,
+                    'R' => Direction::Right,
+                    _ => return Err(invalid()),
+                };
+                let tick = tick.parse().map_err(|_| invalid())?;
+                Ok((tick, dir))
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplayError::Io(err) => write!(f, "could not access replay: {err}"),
+            ReplayError::Format(err) => write!(f, "invalid replay: {err}"),
+            ReplayError::UnsupportedVersion { found } => write!(
+                f,
+                "replay has version {found}, this game reads version {REPLAY_VERSION}"
+            ),
+            ReplayError::Mismatch { points, ticks, expected_points, expected_ticks } => write!(
+                f,
+                "replay ended with {points} points after {ticks} ticks, expected {expected_points} points after {expected_ticks} ticks"
+            ),
+            ReplayError::Corrupt(problem) => write!(f, "invalid replay: {problem}"),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
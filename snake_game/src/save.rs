@@ -0,0 +1,185 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use std::collections::VecDeque;
+
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::board::MAX_SIDE;
+use crate::clock::Clock;
+use crate::level::Level;
+use crate::options::check_board;
+use crate::snake::{Collision, Direction, EdgePolicy, Snake};
+use crate::speed::SpeedCurve;
+use crate::{Game, GameState};
+
+//Version of the save file format, bump it whenever the serialized fields of Game change
+pub const SAVE_VERSION: u32 = 2;
+
+//A save file is JSON: {"version": 2, "game": {...}}. The game includes the RNG state,
+//the queued turns and the clock, so a loaded game continues exactly like the saved one.
+//The occupancy grid is left out and rebuilt on loading, so it always matches the snake.
+#[derive(Serialize)]
+struct SaveFile<'a> {
+    version: u32,
+    game: &'a Game,
+}
+#[derive(Deserialize)]
+struct SaveHeader {
+    version: u32,
+}
+#[derive(Deserialize)]
+struct LoadedSave {
+    game: SavedGame,
+}
+
+//The fields of a Game as they are stored. Every way of deserializing a Game goes through
+//check_game() and rebuilds the occupancy grid, so it always gets a playable game.
+#[derive(Deserialize)]
+pub(crate) struct SavedGame {
+    game_size: (i32, i32),
+    snake: Snake,
+    start_length: i32,
+    edge_policy: EdgePolicy,
+    level: Option<Level>,
+    state: GameState,
+    queued_turns: VecDeque<Direction>,
+    clock: Clock,
+    speed_curve: SpeedCurve,
+    ticks: u64,
+    elapsed: f64,
+    apple_loc: (i32, i32),
+    points: i32,
+    death_cause: Option<Collision>,
+    seed: u64,
+    rng: ChaCha8Rng,
+}
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(io::Error),
+    Format(serde_json::Error),
+    UnsupportedVersion { found: u32 },
+    //Parses, but cannot be played, e.g. a snake outside the board
+    Corrupt(String),
+}
+
+impl Game {
+    pub fn to_save(&self) -> String {
+        let file = SaveFile {
+            version: SAVE_VERSION,
+            game: self,
+        };
+        serde_json::to_string(&file).expect("a game always serializes")
+    }
+    pub fn from_save(text: &str) -> Result<Game, SaveError> {
+        //Check the version first, so an old file gives a clear error instead of a missing field
+        let header: SaveHeader = serde_json::from_str(text).map_err(SaveError::Format)?;
+        if header.version != SAVE_VERSION {
+            return Err(SaveError::UnsupportedVersion { found: header.version });
+        }
+        let save: LoadedSave = serde_json::from_str(text).map_err(SaveError::Format)?;
+        Game::try_from(save.game).map_err(SaveError::Corrupt)
+    }
+    pub fn save(&self, path: &Path) -> Result<(), SaveError> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(SaveError::Io)?;
+        }
+        fs::write(path, self.to_save()).map_err(SaveError::Io)
+    }
+    pub fn load(path: &Path) -> Result<Game, SaveError> {
+        let text = fs::read_to_string(path).map_err(SaveError::Io)?;
+        Game::from_save(&text)
+    }
+    //<data dir>/snake_game/save.json, next to the high scores
+    pub fn default_save_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("snake_game").join("save.json"))
+    }
+}
+
+impl TryFrom<SavedGame> for Game {
+    type Error = String;
+
+    fn try_from(saved: SavedGame) -> Result<Game, String> {
+        check_game(&saved)?;
+        let board = Game::build_board(saved.game_size, &saved.snake, saved.level.as_ref());
+        Ok(Game {
+            game_size: saved.game_size,
+            snake: saved.snake,
+            start_length: saved.start_length,
+            board,
+            edge_policy: saved.edge_policy,
+            level: saved.level,
+            state: saved.state,
+            queued_turns: saved.queued_turns,
+            clock: saved.clock,
+            speed_curve: saved.speed_curve,
+            ticks: saved.ticks,
+            elapsed: saved.elapsed,
+            apple_loc: saved.apple_loc,
+            points: saved.points,
+            death_cause: saved.death_cause,
+            seed: saved.seed,
+            rng: saved.rng,
+        })
+    }
+}
+
+//Everything the rules rely on that serde cannot check
+fn check_game(game: &SavedGame) -> Result<(), String> {
+    let (width, height) = game.game_size;
+    let inside = |(x, y): (i32, i32)| x >= 0 && y >= 0 && x < width && y < height;
+    if width < 1 || height < 1 {
+        return Err("the board is empty".to_string());
+    }
+    if width > MAX_SIDE || height > MAX_SIDE {
+        return Err("the board is too large".to_string());
+    }
+    //Restarts build a new snake of the starting length, levels bring their own
+    if game.level.is_none() {
+        check_board(width, height, game.start_length).map_err(|err| err.to_string())?;
+    }
+    let step_time = game.clock.get_step_time();
+    if !(step_time.is_finite() && step_time > 0.0 && game.clock.get_interval().is_finite()) {
+        return Err("the clock is broken".to_string());
+    }
+    if !game.speed_curve.is_valid() {
+        return Err("the speed curve is broken".to_string());
+    }
+    let level_size = game.level.as_ref().map(|level| level.get_size());
+    if level_size.unwrap_or(game.game_size) != game.game_size
+        || game.snake.get_game_size() != game.game_size
+    {
+        return Err("the board sizes do not match".to_string());
+    }
+    let body = game.snake.get_body();
+    if body.is_empty() {
+        return Err("the snake has no body".to_string());
+    }
+    if !body.iter().all(|block| inside((block.x, block.y))) {
+        return Err("the snake is outside the board".to_string());
+    }
+    if !inside(game.apple_loc) {
+        return Err("the apple is outside the board".to_string());
+    }
+    Ok(())
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SaveError::Io(err) => write!(f, "could not access save file: {err}"),
+            SaveError::Format(err) => write!(f, "invalid save file: {err}"),
+            SaveError::UnsupportedVersion { found } => write!(
+                f,
+                "save file has version {found}, this game reads version {SAVE_VERSION}"
+            ),
+            SaveError::Corrupt(problem) => write!(f, "invalid save file: {problem}"),
+        }
+    }
+}
+
+impl std::error::Error for SaveError {}
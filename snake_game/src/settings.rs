@@ -0,0 +1,208 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::bindings::{BindingConflict, KeyBindings};
+use crate::clock::STEP_TIME;
+use crate::render::{Color, BLOCK_SIZE};
+use crate::speed::{SpeedCurve, MIN_STEP_TIME, SPEED_UP_PER_POINT};
+
+//The user settings file, see settings.example.toml for every option.
+//Every section and value is optional, missing values keep their defaults.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Settings {
+    pub keys: KeyBindings,
+    pub colours: Colours,
+    pub window: WindowOptions,
+    pub speed: SpeedSettings,
+}
+
+//RGBA colours, every component between 0.0 and 1.0
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Colours {
+    pub apple: Color,
+    pub snake: Color,
+    pub background: Color,
+    pub game_over: Color,
+    pub won: Color,
+    pub wall: Color,
+    pub obstacle: Color,
+    pub scores: Color,
+    pub hud_background: Color,
+    pub hud_text: Color,
+    pub pause_overlay: Color,
+    pub pause_text: Color,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WindowOptions {
+    pub title: String,
+    //Size of one board cell in pixels
+    pub block_size: f64,
+    pub vsync: bool,
+    //Anti-aliasing samples, 0 turns it off
+    pub samples: u8,
+    pub max_fps: u64,
+}
+
+//The starting step time and how it shrinks with the points, all in seconds
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SpeedSettings {
+    pub step_time: f64,
+    pub per_point: f64,
+    pub min_step_time: f64,
+}
+
+#[derive(Debug)]
+pub enum SettingsError {
+    Io(io::Error),
+    Parse(toml::de::Error),
+    Conflict(BindingConflict),
+    OutOfRange { setting: String, value: f64, min: f64, max: f64 },
+}
+
+impl Settings {
+    //A missing file gives the default settings
+    pub fn load(path: &Path) -> Result<Settings, SettingsError> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Settings::default()),
+            Err(err) => return Err(SettingsError::Io(err)),
+        };
+        Settings::parse(&text)
+    }
+    pub fn parse(text: &str) -> Result<Settings, SettingsError> {
+        let settings: Settings = toml::from_str(text).map_err(SettingsError::Parse)?;
+        settings.validate()?;
+        Ok(settings)
+    }
+    //<config dir>/snake_game/settings.toml, e.g. ~/.config on Linux
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("snake_game").join("settings.toml"))
+    }
+
+    //private functions
+    fn validate(&self) -> Result<(), SettingsError> {
+        self.keys.validate().map_err(SettingsError::Conflict)?;
+        for (name, colour) in self.colours.named() {
+            for value in colour {
+                check_range(&format!("colours.{name}"), value as f64, 0.0, 1.0)?;
+            }
+        }
+        check_range("window.block_size", self.window.block_size, 5.0, 100.0)?;
+        check_range("window.max_fps", self.window.max_fps as f64, 1.0, 1000.0)?;
+        check_range("speed.step_time", self.speed.step_time, 0.01, 5.0)?;
+        check_range("speed.per_point", self.speed.per_point, 0.0, 1.0)?;
+        check_range("speed.min_step_time", self.speed.min_step_time, 0.01, self.speed.step_time)?;
+        Ok(())
+    }
+}
+
+fn check_range(setting: &str, value: f64, min: f64, max: f64) -> Result<(), SettingsError> {
+    if (min..=max).contains(&value) {
+        return Ok(());
+    }
+    Err(SettingsError::OutOfRange {
+        setting: setting.to_string(),
+        value,
+        min,
+        max,
+    })
+}
+
+impl Colours {
+    fn named(&self) -> [(&'static str, Color); 12] {
+        [
+            ("apple", self.apple),
+            ("snake", self.snake),
+            ("background", self.background),
+            ("game_over", self.game_over),
+            ("won", self.won),
+            ("wall", self.wall),
+            ("obstacle", self.obstacle),
+            ("scores", self.scores),
+            ("hud_background", self.hud_background),
+            ("hud_text", self.hud_text),
+            ("pause_overlay", self.pause_overlay),
+            ("pause_text", self.pause_text),
+        ]
+    }
+}
+
+impl Default for Colours {
+    fn default() -> Self {
+        Colours {
+            apple: [0.95, 0.30, 0.1, 1.0],
+            snake: [0.18, 0.80, 0.44, 1.0],
+            background: [0.204, 0.286, 0.369, 1.0],
+            game_over: [0.91, 0.30, 0.24, 0.5],
+            won: [0.95, 0.77, 0.06, 0.8],
+            wall: [0.50, 0.55, 0.55, 1.0],
+            obstacle: [0.44, 0.33, 0.25, 1.0],
+            scores: [0.93, 0.94, 0.95, 0.9],
+            hud_background: [0.17, 0.24, 0.31, 1.0],
+            hud_text: [0.93, 0.94, 0.95, 1.0],
+            pause_overlay: [0.0, 0.0, 0.0, 0.5],
+            pause_text: [0.93, 0.94, 0.95, 1.0],
+        }
+    }
+}
+
+impl Default for WindowOptions {
+    fn default() -> Self {
+        WindowOptions {
+            title: "Snake".to_string(),
+            block_size: BLOCK_SIZE,
+            vsync: false,
+            samples: 0,
+            max_fps: 60,
+        }
+    }
+}
+
+impl SpeedSettings {
+    //The curve starting at `step_time`, or staying there if `fixed`
+    pub fn curve(&self, step_time: f64, fixed: bool) -> SpeedCurve {
+        if fixed {
+            return SpeedCurve::Fixed(step_time);
+        }
+        SpeedCurve::Linear {
+            start: step_time,
+            per_point: self.per_point,
+            min: self.min_step_time.min(step_time),
+        }
+    }
+}
+
+impl Default for SpeedSettings {
+    fn default() -> Self {
+        SpeedSettings {
+            step_time: STEP_TIME,
+            per_point: SPEED_UP_PER_POINT,
+            min_step_time: MIN_STEP_TIME,
+        }
+    }
+}
+
+impl fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SettingsError::Io(err) => write!(f, "could not read settings: {err}"),
+            SettingsError::Parse(err) => write!(f, "invalid settings: {err}"),
+            SettingsError::Conflict(conflict) => write!(f, "invalid key bindings: {conflict}"),
+            SettingsError::OutOfRange { setting, value, min, max } => write!(
+                f,
+                "{setting} is {value}, expected a value from {min} to {max}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SettingsError {}
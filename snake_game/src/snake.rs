@@ -1,29 +1,44 @@
-use std::collections::LinkedList;
+use std::collections::VecDeque;
+use std::fmt;
 
-#[derive(Debug, Clone)]
+use serde::{Deserialize, Serialize};
+
+use crate::board::Board;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Snake {
-    body: LinkedList<Block>,
+    body: VecDeque<Block>,
     game_size: (i32, i32),
     pub prev_dir: Direction,
 }
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Block {
     pub x: i32,
     pub y: i32,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Direction {
     Up,
     Down,
     Left,
     Right,
 }
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Collision {
     None,
     Apple,
     Snake,
+    Wall,
+    Obstacle,
+}
+//What happens when the snake moves past the edge of the board
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum EdgePolicy {
+    //Appear on the opposite side (the board is a torus)
+    Wrap,
+    //The edge is a wall that kills the snake
+    Solid,
 }
 
 impl Snake {
@@ -35,7 +50,7 @@ impl Snake {
         default_move_dir: Direction,
         game_size: (i32, i32),
     ) -> Self {
-        let mut body = LinkedList::new();
+        let mut body = VecDeque::with_capacity(size.max(0) as usize);
         for i in 0..size {
             body.push_back(Block { x: x - i, y });
         }
@@ -45,6 +60,18 @@ impl Snake {
             game_size,
         }
     }
+    //Builds a snake from explicit cells, head first
+    pub fn from_body(
+        cells: &[(i32, i32)],
+        default_move_dir: Direction,
+        game_size: (i32, i32),
+    ) -> Self {
+        Snake {
+            body: cells.iter().map(|&(x, y)| Block { x, y }).collect(),
+            prev_dir: default_move_dir,
+            game_size,
+        }
+    }
     pub fn init_snake(size: i32, default_move_dir: Direction, game_size: (i32, i32)) -> Snake {
         let x_loc = (game_size.0 / 2).abs();
         let y_loc = (game_size.1 / 2).abs();
@@ -52,18 +79,17 @@ impl Snake {
     }
 
     //Snake Logic - public
-    pub(crate) fn move_snake(&mut self, dir: &Direction) {
-        match &dir {
-            Direction::Up => self.perform_move_snake(0, -1),
-            Direction::Down => self.perform_move_snake(0, 1),
-            Direction::Left => self.perform_move_snake(-1, 0),
-            Direction::Right => self.perform_move_snake(1, 0),
-        }
-    }
-    pub(crate) fn check_collision(&self, apple_loc: (i32, i32)) -> Collision {
-        let mut tmp = self.body.clone();
-        tmp.pop_front();
-        if Snake::intersects_body(&tmp, self.get_head_pos()) {
+    //Returns false, without moving, if a solid wall is in the way
+    pub(crate) fn move_snake(&mut self, dir: &Direction, edges: EdgePolicy, board: &mut Board) -> bool {
+        let (delta_x, delta_y) = dir.delta();
+        self.perform_move_snake(delta_x, delta_y, edges, board)
+    }
+    pub(crate) fn check_collision(&self, apple_loc: (i32, i32), board: &Board) -> Collision {
+        if board.is_obstacle(self.get_head_pos()) {
+            return Collision::Obstacle;
+        }
+        //The head counts as one occupant, anything more is another body part
+        if board.occupants(self.get_head_pos()) > 1 {
             return Collision::Snake;
         }
         if self.get_head_pos() == apple_loc {
@@ -71,17 +97,28 @@ impl Snake {
         }
         Collision::None
     }
-    pub(crate) fn grow_snake(&mut self) {
+    pub(crate) fn grow_snake(&mut self, board: &mut Board) {
         let (x, y) = self.get_tail_pos();
         self.body.push_back(Block { x, y });
+        board.occupy((x, y));
     }
 
     //Snake logic - private
-    fn perform_move_snake(&mut self, delta_x: i32, delta_y: i32) {
-        let target =
-            Snake::calculate_next_position(self.get_head_pos(), delta_x, delta_y, self.game_size);
-        self.body.pop_back();
+    fn perform_move_snake(&mut self, delta_x: i32, delta_y: i32, edges: EdgePolicy, board: &mut Board) -> bool {
+        let target = Snake::calculate_next_position(
+            self.get_head_pos(),
+            delta_x,
+            delta_y,
+            self.game_size,
+            edges,
+        );
+        let Some(target) = target else { return false };
+        if let Some(tail) = self.body.pop_back() {
+            board.vacate((tail.x, tail.y));
+        }
+        board.occupy((target.x, target.y));
         self.body.push_front(target);
+        true
     }
 
     //Getters
@@ -100,52 +137,33 @@ impl Snake {
     pub fn get_length(&self) -> usize {
         self.body.len()
     }
-    pub fn get_body(&self) -> &LinkedList<Block> {
+    pub fn get_body(&self) -> &VecDeque<Block> {
         &self.body
     }
+    pub fn get_game_size(&self) -> (i32, i32) {
+        self.game_size
+    }
 
     //Static
-    fn calculate_next_position(current_pos: (i32, i32),delta_x: i32,delta_y: i32,
-                               game_size: (i32, i32),) -> Block {
+    //Returns None when the target is outside the board and the edges are solid
+    pub(crate) fn calculate_next_position(current_pos: (i32, i32),delta_x: i32,delta_y: i32,
+                               game_size: (i32, i32), edges: EdgePolicy,) -> Option<Block> {
         let (head_x, head_y) = current_pos;
         let target = Block {
             x: head_x + delta_x,
             y: head_y + delta_y,
         };
-        if target.x >= game_size.0 {
-            return Block {
-                x: 0,
-                y: head_y + delta_y,
-            };
-        }
-        if target.x < 0 {
-            return Block {
-                x: game_size.0 - 1,
-                y: head_y + delta_y,
-            };
-        }
-        if target.y >= game_size.1 {
-            return Block {
-                x: head_x + delta_x,
-                y: 0,
-            };
+        let inside = (0..game_size.0).contains(&target.x) && (0..game_size.1).contains(&target.y);
+        if inside {
+            return Some(target);
         }
-        if target.y < 0 {
-            return Block {
-                x: head_x + delta_x,
-                y: game_size.0 - 1,
-            };
+        match edges {
+            EdgePolicy::Solid => None,
+            EdgePolicy::Wrap => Some(Block {
+                x: target.x.rem_euclid(game_size.0),
+                y: target.y.rem_euclid(game_size.1),
+            }),
         }
-        target
-    }
-
-    pub(crate) fn intersects_body(body: &LinkedList<Block>, other: (i32, i32)) -> bool {
-        for body_part in body {
-            if *body_part == other {
-                return true;
-            }
-        }
-        return false;
     }
 }
 
@@ -160,6 +178,22 @@ impl PartialEq<(i32, i32)> for Block {
     }
 }
 impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ];
+
+    //One cell in this direction, as (delta x, delta y)
+    pub(crate) fn delta(&self) -> (i32, i32) {
+        match self {
+            Self::Up => (0, -1),
+            Self::Down => (0, 1),
+            Self::Left => (-1, 0),
+            Self::Right => (1, 0),
+        }
+    }
     pub(crate) fn opposite(&self) -> Direction {
         match self {
             Self::Up => Direction::Down,
@@ -169,3 +203,48 @@ impl Direction {
         }
     }
 }
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Direction::Up => write!(f, "up"),
+            Direction::Down => write!(f, "down"),
+            Direction::Left => write!(f, "left"),
+            Direction::Right => write!(f, "right"),
+        }
+    }
+}
+
+impl std::str::FromStr for Direction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "up" => Ok(Direction::Up),
+            "down" => Ok(Direction::Down),
+            "left" => Ok(Direction::Left),
+            "right" => Ok(Direction::Right),
+            invalid => Err(format!("unknown direction '{invalid}', expected up, down, left or right")),
+        }
+    }
+}
+
+impl fmt::Display for EdgePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EdgePolicy::Wrap => write!(f, "wrap"),
+            EdgePolicy::Solid => write!(f, "solid"),
+        }
+    }
+}
+
+impl std::str::FromStr for EdgePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "wrap" => Ok(EdgePolicy::Wrap),
+            "solid" => Ok(EdgePolicy::Solid),
+            invalid => Err(format!("unknown edge mode '{invalid}', expected wrap or solid")),
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+
+use crate::clock::STEP_TIME;
+
+//Shape of the default curve
+pub const SPEED_UP_PER_POINT: f64 = 0.0043;
+pub const MIN_STEP_TIME: f64 = 0.08;
+
+//How the tick interval (in seconds) shrinks as the player scores points.
+//Every curve is capped by `min`, so the game never gets faster than that.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SpeedCurve {
+    //Same interval for the whole game
+    Fixed(f64),
+    //Shrinks by `per_point` for every point
+    Linear { start: f64, per_point: f64, min: f64 },
+    //Shrinks by `decrease` every `every` points
+    Stepped { start: f64, every: i32, decrease: f64, min: f64 },
+    //(points, interval) pairs: the interval of the highest reached threshold is used
+    Custom { table: Vec<(i32, f64)>, min: f64 },
+}
+
+impl SpeedCurve {
+    pub fn step_time(&self, points: i32) -> f64 {
+        let points = points.max(0);
+        match self {
+            SpeedCurve::Fixed(step_time) => *step_time,
+            SpeedCurve::Linear { start, per_point, min } => {
+                (start - per_point * points as f64).max(*min)
+            }
+            SpeedCurve::Stepped { start, every, decrease, min } => {
+                let steps = points / (*every).max(1);
+                (start - decrease * steps as f64).max(*min)
+            }
+            SpeedCurve::Custom { table, min } => {
+                let reached = table
+                    .iter()
+                    .filter(|(threshold, _)| *threshold <= points)
+                    .max_by_key(|(threshold, _)| *threshold);
+                reached.map_or(STEP_TIME, |(_, step_time)| *step_time).max(*min)
+            }
+        }
+    }
+
+    //False if some interval could be zero, negative or not a number, e.g. in a broken save
+    pub fn is_valid(&self) -> bool {
+        let positive = |step_time: f64| step_time.is_finite() && step_time > 0.0;
+        match self {
+            SpeedCurve::Fixed(step_time) => positive(*step_time),
+            SpeedCurve::Linear { start, per_point, min } => {
+                positive(*min) && start.is_finite() && per_point.is_finite()
+            }
+            SpeedCurve::Stepped { start, decrease, min, .. } => {
+                positive(*min) && start.is_finite() && decrease.is_finite()
+            }
+            SpeedCurve::Custom { table, min } => {
+                positive(*min) && table.iter().all(|(_, step_time)| step_time.is_finite())
+            }
+        }
+    }
+}
+
+impl Default for SpeedCurve {
+    //Starts at the classic 5 ticks per second and tops out at 12.5 after 28 apples
+    fn default() -> Self {
+        SpeedCurve::Linear {
+            start: STEP_TIME,
+            per_point: SPEED_UP_PER_POINT,
+            min: MIN_STEP_TIME,
+        }
+    }
+}
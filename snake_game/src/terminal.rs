@@ -0,0 +1,338 @@
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{
+    self, DisableFocusChange, EnableFocusChange, Event, KeyCode, KeyEvent, KeyEventKind,
+};
+use crossterm::style::{
+    Color as TermColour, Print, ResetColor, SetBackgroundColor, SetForegroundColor,
+};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+use piston_window::Key;
+
+use crate::autopilot::Autopilot;
+use crate::bindings::{frontend_action, Action};
+use crate::render::{border_width, press_hint, screen_size, Cell, Color, Renderer, HUD_ROWS};
+use crate::replay::{Player, Recorder, Replay};
+use crate::settings::Settings;
+use crate::Game;
+
+//Every board cell is two terminal columns wide, so cells look roughly square
+const SNAKE_CELL: &str = "██";
+const APPLE_CELL: &str = "()";
+const OBSTACLE_CELL: &str = "▒▒";
+const WALL_CELL: &str = "▓▓";
+
+//How long to wait for input before drawing the next frame
+const FRAME_TIME: Duration = Duration::from_millis(16);
+
+//Draws into a grid of characters, two per block, and writes it to `out` on present()
+struct TerminalRenderer<W: Write> {
+    out: W,
+    size: (i32, i32),
+    background: Color,
+    screen: Vec<Vec<Glyph>>,
+    //What the terminal shows right now
+    shown: Vec<Vec<Glyph>>,
+}
+
+//One character with opaque colours
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Glyph {
+    ch: char,
+    fg: Color,
+    bg: Color,
+}
+
+//Plays the game in the terminal, e.g. over SSH. Uses the same key bindings as the window.
+pub fn play_terminal(game: Game, settings: &Settings, autopilot: Option<Autopilot>) -> io::Result<()> {
+    run(game, settings, None, autopilot)
+}
+
+//Shows a recorded game in the terminal; only pausing and quitting work while it plays
+pub fn play_replay_terminal(replay: Replay, settings: &Settings) -> io::Result<()> {
+    run(
+        replay.start_game(),
+        settings,
+        Some(Player::new(replay)),
+        None,
+    )
+}
+
+//Maps a terminal key to the piston key of the same name, so one set of bindings serves both frontends
+pub fn key_from_terminal(code: KeyCode) -> Option<Key> {
+    let key = match code {
+        KeyCode::Char(' ') => Key::Space,
+        KeyCode::Char(c) if c.is_ascii_alphanumeric() => Key::from(c.to_ascii_lowercase() as u32),
+        KeyCode::Up => Key::Up,
+        KeyCode::Down => Key::Down,
+        KeyCode::Left => Key::Left,
+        KeyCode::Right => Key::Right,
+        KeyCode::Enter => Key::Return,
+        KeyCode::Esc => Key::Escape,
+        KeyCode::Backspace => Key::Backspace,
+        KeyCode::Tab => Key::Tab,
+        KeyCode::F(n @ 1..=12) => Key::from(Key::F1 as u32 + n as u32 - 1),
+        _ => return None,
+    };
+    Some(key)
+}
+
+//The frame as plain text, one string per terminal line: two HUD lines, then the board
+pub fn frame_lines(game: &Game, settings: &Settings) -> Vec<String> {
+    let mut renderer = TerminalRenderer::new(io::sink(), screen_size_of(game));
+    draw(&mut renderer, game, settings, &hints(settings));
+    renderer.lines()
+}
+
+//Restores the terminal even if the game panics
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn enter() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), EnterAlternateScreen, EnableFocusChange, Hide)?;
+        Ok(TerminalGuard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = execute!(
+            io::stdout(),
+            ResetColor,
+            Show,
+            DisableFocusChange,
+            LeaveAlternateScreen
+        );
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+struct Hints {
+    resume: String,
+    restart: String,
+}
+
+fn hints(settings: &Settings) -> Hints {
+    Hints {
+        resume: press_hint(&settings.keys.pause, "resume"),
+        restart: press_hint(&settings.keys.restart, "restart"),
+    }
+}
+
+fn screen_size_of(game: &Game) -> (i32, i32) {
+    screen_size(game.get_game_size(), border_width(game.get_edge_policy()))
+}
+
+//The game, plus the restart hint that the window shows under the high-score table
+fn draw(r: &mut impl Renderer, game: &Game, settings: &Settings, hints: &Hints) {
+    game.draw(r, &settings.colours, &hints.resume);
+    if game.is_over() {
+        let colour = settings.colours.scores;
+        r.draw_text(colour, 20, (1.0, HUD_ROWS as f64 + 8.0), &hints.restart);
+    }
+}
+
+fn run(
+    mut game: Game,
+    settings: &Settings,
+    mut player: Option<Player>,
+    mut autopilot: Option<Autopilot>,
+) -> io::Result<()> {
+    let _guard = TerminalGuard::enter()?;
+    let mut renderer = TerminalRenderer::new(io::stdout(), screen_size_of(&game));
+    execute!(renderer.out, Clear(ClearType::All))?;
+
+    let hints = hints(settings);
+    let mut recorder = Recorder::new(&game);
+    let mut last_time = Instant::now();
+    loop {
+        if event::poll(FRAME_TIME)? {
+            match event::read()? {
+                //Stop the clock when the player switches to another window
+                Event::FocusLost => game.pause(),
+                Event::Key(KeyEvent {
+                    code,
+                    kind: KeyEventKind::Press,
+                    ..
+                }) => {
+                    let action = key_from_terminal(code)
+                        .and_then(|key| settings.keys.action(key, game.is_over()));
+                    match frontend_action(action, &mut game, player.is_some(), autopilot.is_some()) {
+                        Some(Action::Quit) => return Ok(()),
+                        //Saving and loading are only offered in the window
+                        Some(Action::Save | Action::Load) | None => {}
+                        Some(action) => recorder.apply(&mut game, action),
+                    }
+                }
+                //Draw everything again after a resize
+                Event::Resize(..) => {
+                    execute!(renderer.out, Clear(ClearType::All))?;
+                    renderer.invalidate();
+                }
+                _ => {}
+            }
+        }
+
+        let now = Instant::now();
+        match player.as_mut() {
+            Some(player) => player.feed(&mut game),
+            None => {
+                if let Some(dir) = autopilot
+                    .as_mut()
+                    .and_then(|autopilot| autopilot.next_move(&game))
+                {
+                    recorder.apply(&mut game, Action::Move(dir));
+                }
+                //Raw mode does not turn \n into a new line, so end the message with \r\n
+                if let Some(Err(message)) = recorder.finished(&game).map(Replay::save_as_last) {
+                    eprint!("{message}\r\n");
+                }
+            }
+        }
+        game.update((now - last_time).as_secs_f64());
+        last_time = now;
+
+        draw(&mut renderer, &game, settings, &hints);
+        renderer.present()?;
+    }
+}
+
+impl<W: Write> TerminalRenderer<W> {
+    //`size` is the screen size in blocks, see render::screen_size
+    fn new(out: W, size: (i32, i32)) -> Self {
+        TerminalRenderer {
+            out,
+            size,
+            background: [0.0, 0.0, 0.0, 1.0],
+            screen: Vec::new(),
+            shown: Vec::new(),
+        }
+    }
+
+    //Makes sure the next present() writes every line, e.g. after a resize
+    fn invalidate(&mut self) {
+        self.shown.clear();
+    }
+
+    //The screen as plain text, one string per line
+    fn lines(&self) -> Vec<String> {
+        let text = |row: &Vec<Glyph>| row.iter().map(|glyph| glyph.ch).collect();
+        self.screen.iter().map(text).collect()
+    }
+
+    //Grows the screen if needed, text may run past the board
+    fn glyph(&mut self, column: usize, row: usize) -> &mut Glyph {
+        let blank = Glyph {
+            ch: ' ',
+            fg: self.background,
+            bg: self.background,
+        };
+        if self.screen.len() <= row {
+            self.screen.resize(row + 1, Vec::new());
+        }
+        let line = &mut self.screen[row];
+        if line.len() <= column {
+            line.resize(column + 1, blank);
+        }
+        &mut line[column]
+    }
+}
+
+impl<W: Write> Renderer for TerminalRenderer<W> {
+    fn clear(&mut self, colour: Color) {
+        self.background = blend(colour, [0.0, 0.0, 0.0, 1.0]);
+        let blank = Glyph {
+            ch: ' ',
+            fg: self.background,
+            bg: self.background,
+        };
+        let (width, height) = self.size;
+        self.screen = vec![vec![blank; 2 * width as usize]; height as usize];
+    }
+    fn draw_cell(&mut self, cell: Cell, colour: Color, x: i32, y: i32) {
+        if x < 0 || y < 0 {
+            return;
+        }
+        let text = match cell {
+            Cell::Fill => None,
+            Cell::Snake => Some(SNAKE_CELL),
+            Cell::Apple => Some(APPLE_CELL),
+            Cell::Obstacle => Some(OBSTACLE_CELL),
+            Cell::Wall => Some(WALL_CELL),
+        };
+        for (i, column) in [2 * x as usize, 2 * x as usize + 1].into_iter().enumerate() {
+            let glyph = self.glyph(column, y as usize);
+            match text {
+                //A fill tints whatever is already there
+                None => {
+                    glyph.bg = blend(colour, glyph.bg);
+                    glyph.fg = blend(colour, glyph.fg);
+                }
+                //Everything else is drawn as block characters in the cell colour
+                Some(text) => {
+                    glyph.ch = text.chars().nth(i).unwrap_or(' ');
+                    glyph.fg = blend(colour, glyph.bg);
+                }
+            }
+        }
+    }
+    //Terminal text has one size, the row is the block holding the baseline
+    fn draw_text(&mut self, colour: Color, _size: u32, pos: (f64, f64), text: &str) {
+        let row = (pos.1.ceil() as usize).saturating_sub(1);
+        let column = (pos.0 * 2.0).round().max(0.0) as usize;
+        for (i, ch) in text.chars().enumerate() {
+            let glyph = self.glyph(column + i, row);
+            glyph.ch = ch;
+            glyph.fg = blend(colour, glyph.bg);
+        }
+    }
+    //Only writes to the terminal when something changed, which keeps SSH traffic low
+    fn present(&mut self) -> io::Result<()> {
+        if self.screen == self.shown {
+            return Ok(());
+        }
+        for (row, line) in self.screen.iter().enumerate() {
+            queue!(self.out, MoveTo(0, row as u16))?;
+            let mut colours = None;
+            for glyph in line {
+                if colours != Some((glyph.bg, glyph.fg)) {
+                    colours = Some((glyph.bg, glyph.fg));
+                    queue!(
+                        self.out,
+                        SetBackgroundColor(to_terminal(glyph.bg)),
+                        SetForegroundColor(to_terminal(glyph.fg))
+                    )?;
+                }
+                queue!(self.out, Print(glyph.ch))?;
+            }
+            queue!(self.out, ResetColor, Clear(ClearType::UntilNewLine))?;
+        }
+        queue!(
+            self.out,
+            MoveTo(0, self.screen.len() as u16),
+            Clear(ClearType::FromCursorDown)
+        )?;
+        self.shown = self.screen.clone();
+        self.out.flush()
+    }
+}
+
+//Terminals have no transparency, so blend the colour over what it is drawn on
+fn blend(colour: Color, under: Color) -> Color {
+    let [r, g, b, a] = colour;
+    let mix = |c: f32, u: f32| (c * a + u * (1.0 - a)).clamp(0.0, 1.0);
+    [mix(r, under[0]), mix(g, under[1]), mix(b, under[2]), 1.0]
+}
+
+fn to_terminal(colour: Color) -> TermColour {
+    let channel = |c: f32| (c * 255.0).round() as u8;
+    TermColour::Rgb {
+        r: channel(colour[0]),
+        g: channel(colour[1]),
+        b: channel(colour[2]),
+    }
+}
@@ -0,0 +1,256 @@
+use std::fmt;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use clap::Parser;
+
+use crate::agent::{Agent, GameView};
+use crate::autopilot::{agent_for, Autopilot, AutopilotMode};
+use crate::external::DEFAULT_TIMEOUT;
+use crate::level::Level;
+use crate::options::{check_board, OptionsError};
+use crate::snake::{Collision, EdgePolicy};
+use crate::{Game, GameState};
+
+//Command-line options of the tournament binary
+#[derive(Debug, Clone, Parser)]
+#[command(
+    name = "tournament",
+    about = "Plays the same seeded games with each bot and compares the results"
+)]
+pub struct TournamentOptions {
+    /// Bots to compare, separated by commas
+    #[arg(long, value_delimiter = ',', default_value = "path,cycle")]
+    pub bots: Vec<AutopilotMode>,
+    /// A bot in another program to compare as well, e.g. "python3 bots/greedy.py"; can be repeated
+    #[arg(long, value_name = "COMMAND")]
+    pub external: Vec<String>,
+    /// Milliseconds an external bot may take for a move before the snake goes straight on
+    #[arg(long, value_name = "MS", default_value_t = DEFAULT_TIMEOUT.as_millis() as u64,
+          value_parser = clap::value_parser!(u64).range(1..))]
+    pub bot_timeout: u64,
+    /// Games per bot
+    #[arg(long, default_value_t = 20, value_parser = clap::value_parser!(u32).range(1..))]
+    pub games: u32,
+    /// Seed of the first game, the others count up from it
+    #[arg(long, default_value_t = 0)]
+    pub seed: u64,
+    /// Ticks after which a game is stopped
+    #[arg(long, default_value_t = 100_000)]
+    pub max_ticks: u64,
+    /// Board width in cells
+    #[arg(long, default_value_t = 20, conflicts_with = "level")]
+    pub width: i32,
+    /// Board height in cells
+    #[arg(long, default_value_t = 20, conflicts_with = "level")]
+    pub height: i32,
+    /// Starting length of the snake
+    #[arg(long, default_value_t = 3, conflicts_with = "level")]
+    pub length: i32,
+    /// What happens at the edge of the board: wrap or solid
+    #[arg(long, default_value_t = EdgePolicy::Wrap)]
+    pub edges: EdgePolicy,
+    /// Level file to play instead of an empty board
+    #[arg(long)]
+    pub level: Option<PathBuf>,
+}
+
+//Every agent plays the same games: the board of `template` with the seeds `seed`, `seed + 1`, ...
+#[derive(Debug, Clone)]
+pub struct Tournament {
+    template: Game,
+    games: u32,
+    seed: u64,
+    max_ticks: u64,
+}
+
+//How one game of a tournament went
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameResult {
+    pub seed: u64,
+    pub points: i32,
+    //Ticks survived, or taken to win
+    pub ticks: u64,
+    pub ending: Ending,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ending {
+    Won,
+    HitItself,
+    HitWall,
+    HitObstacle,
+    //Still alive after the maximum number of ticks
+    OutOfTime,
+}
+
+const ENDINGS: [Ending; 5] = [
+    Ending::Won,
+    Ending::HitItself,
+    Ending::HitWall,
+    Ending::HitObstacle,
+    Ending::OutOfTime,
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub agent: String,
+    pub results: Vec<GameResult>,
+}
+
+impl TournamentOptions {
+    //Checks the options and builds the tournament they describe
+    pub fn build(&self) -> Result<Tournament, OptionsError> {
+        let mut template = match &self.level {
+            Some(path) => {
+                let level = Level::load(path).map_err(|error| OptionsError::Level {
+                    path: path.clone(),
+                    error,
+                })?;
+                Game::from_level(&level, self.seed)
+            }
+            None => {
+                check_board(self.width, self.height, self.length)?;
+                Game::with_length(self.width, self.height, self.length, self.seed)
+            }
+        };
+        template.set_edge_policy(self.edges);
+        Ok(Tournament::new(
+            template,
+            self.games,
+            self.seed,
+            self.max_ticks,
+        ))
+    }
+
+    //Getters
+    pub fn get_bot_timeout(&self) -> Duration {
+        Duration::from_millis(self.bot_timeout)
+    }
+}
+
+impl Tournament {
+    //Construction
+    pub fn new(template: Game, games: u32, seed: u64, max_ticks: u64) -> Self {
+        Tournament {
+            template,
+            games,
+            seed,
+            max_ticks,
+        }
+    }
+
+    //The game of round `round`, the same for every agent
+    pub fn game(&self, round: u32) -> Game {
+        self.template.reseeded(self.seed.wrapping_add(round as u64))
+    }
+    //The built-in bot for `mode`, None if it cannot play this board
+    pub fn bot(&self, mode: AutopilotMode) -> Option<Box<dyn Agent>> {
+        agent_for(mode, &GameView::new(&self.game(0)))
+    }
+
+    //Plays every round without a frontend
+    pub fn play(&self, agent: Box<dyn Agent>) -> Report {
+        let mut autopilot = Autopilot::with_agent(agent);
+        let results = (0..self.games)
+            .map(|round| {
+                let mut game = self.game(round);
+                autopilot.reset(&game);
+                autopilot.play(&mut game, self.max_ticks);
+                GameResult::from_game(&game)
+            })
+            .collect();
+        Report {
+            agent: autopilot.get_name().to_string(),
+            results,
+        }
+    }
+}
+
+impl GameResult {
+    pub fn from_game(game: &Game) -> Self {
+        let ending = match (game.get_state(), game.get_death_cause()) {
+            (GameState::Won, _) => Ending::Won,
+            (GameState::Dead, Some(Collision::Snake)) => Ending::HitItself,
+            (GameState::Dead, Some(Collision::Wall)) => Ending::HitWall,
+            (GameState::Dead, Some(Collision::Obstacle)) => Ending::HitObstacle,
+            _ => Ending::OutOfTime,
+        };
+        GameResult {
+            seed: game.get_seed(),
+            points: game.get_points(),
+            ticks: game.get_ticks(),
+            ending,
+        }
+    }
+}
+
+impl Report {
+    pub fn mean_points(&self) -> f64 {
+        mean(self.results.iter().map(|result| result.points as f64))
+    }
+    //The middle score, or the mean of the two middle ones for an even number of games
+    pub fn median_points(&self) -> f64 {
+        let mut points: Vec<i32> = self.results.iter().map(|result| result.points).collect();
+        points.sort_unstable();
+        match points.len() {
+            0 => 0.0,
+            n if n % 2 == 1 => points[n / 2] as f64,
+            n => (points[n / 2 - 1] + points[n / 2]) as f64 / 2.0,
+        }
+    }
+    pub fn mean_ticks(&self) -> f64 {
+        mean(self.results.iter().map(|result| result.ticks as f64))
+    }
+    //Number of games that ended this way
+    pub fn count(&self, ending: Ending) -> usize {
+        self.results
+            .iter()
+            .filter(|result| result.ending == ending)
+            .count()
+    }
+}
+
+fn mean(values: impl ExactSizeIterator<Item = f64>) -> f64 {
+    match values.len() {
+        0 => 0.0,
+        n => values.sum::<f64>() / n as f64,
+    }
+}
+
+impl fmt::Display for Ending {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Ending::Won => write!(f, "won"),
+            Ending::HitItself => write!(f, "hit itself"),
+            Ending::HitWall => write!(f, "hit the wall"),
+            Ending::HitObstacle => write!(f, "hit an obstacle"),
+            Ending::OutOfTime => write!(f, "out of time"),
+        }
+    }
+}
+
+//e.g.
+//path: 20 games
+//  points: mean 312.4, median 320
+//  ticks survived: mean 5231.0
+//  endings: 12 won, 5 hit itself, 3 out of time
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}: {} games", self.agent, self.results.len())?;
+        writeln!(
+            f,
+            "  points: mean {:.1}, median {}",
+            self.mean_points(),
+            self.median_points()
+        )?;
+        writeln!(f, "  ticks survived: mean {:.1}", self.mean_ticks())?;
+        let endings: Vec<String> = ENDINGS
+            .iter()
+            .map(|&ending| (ending, self.count(ending)))
+            .filter(|&(_, count)| count > 0)
+            .map(|(ending, count)| format!("{count} {ending}"))
+            .collect();
+        write!(f, "  endings: {}", endings.join(", "))
+    }
+}
@@ -0,0 +1,277 @@
+use std::path::PathBuf;
+
+use crate::autopilot::Autopilot;
+use crate::bindings::{frontend_action, Action};
+use crate::highscore::{today, HighScores, ScoreEntry, MAX_INITIALS};
+use crate::replay::{Player, Recorder, Replay};
+use crate::render::{border_width, press_hint, to_gui_coord, window_size, Cell, Color, Renderer, BLOCK_SIZE, HUD_ROWS};
+use crate::settings::{Colours, Settings};
+use crate::Game;
+
+use piston_window::{
+    clear, rectangle, text, EventLoop, Transformed, Button, Context, FocusEvent, G2d, Key, PistonWindow,
+    PressEvent, UpdateEvent, Window, WindowSettings, Glyphs,
+};
+
+//Draws into a piston window, which shows the frame once the draw callback returns
+struct PistonRenderer<'a, 'b> {
+    ctx: Context,
+    g: &'a mut G2d<'b>,
+    glyphs: &'a mut Glyphs,
+}
+
+impl Renderer for PistonRenderer<'_, '_> {
+    fn clear(&mut self, colour: Color) {
+        clear(colour, self.g);
+    }
+    fn draw_cell(&mut self, _cell: Cell, colour: Color, x: i32, y: i32) {
+        let rect = [to_gui_coord(x), to_gui_coord(y), BLOCK_SIZE, BLOCK_SIZE];
+        rectangle(colour, rect, self.ctx.transform, self.g);
+    }
+    fn draw_text(&mut self, colour: Color, size: u32, pos: (f64, f64), line: &str) {
+        let transform = self.ctx.transform.trans(pos.0 * BLOCK_SIZE, pos.1 * BLOCK_SIZE);
+        text::Text::new_color(colour, size)
+            .draw(line, self.glyphs, &self.ctx.draw_state, transform, self.g)
+            .unwrap();
+    }
+}
+
+fn save_game(game: &Game) {
+    let Some(path) = Game::default_save_path() else { return };
+    if let Err(err) = game.save(&path) {
+        eprintln!("Could not save the game to {}: {err}", path.display());
+    }
+}
+
+//Loads the saved game paused, as long as it fits in the current window
+fn load_game(current: &Game) -> Option<Game> {
+    let path = Game::default_save_path()?;
+    let mut game = match Game::load(&path) {
+        Ok(game) => game,
+        Err(err) => {
+            eprintln!("Could not load the game from {}: {err}", path.display());
+            return None;
+        }
+    };
+    if game.get_game_size() != current.get_game_size()
+        || game.get_edge_policy() != current.get_edge_policy()
+    {
+        eprintln!("The saved game is for a different board, start it with --load instead");
+        return None;
+    }
+    game.pause();
+    Some(game)
+}
+
+//`scale` multiplies the block size from the settings, with `autopilot` the game plays itself
+pub fn play_game(game: Game, settings: &Settings, scale: f64, autopilot: Option<Autopilot>) {
+    run(game, settings, scale, None, autopilot);
+}
+
+//Shows a recorded game; only pausing and quitting work while it plays
+pub fn play_replay(replay: Replay, settings: &Settings, scale: f64) {
+    run(replay.start_game(), settings, scale, Some(Player::new(replay)), None);
+}
+
+fn run(
+    mut game: Game,
+    settings: &Settings,
+    scale: f64,
+    mut player: Option<Player>,
+    mut autopilot: Option<Autopilot>,
+) {
+    let options = &settings.window;
+    let colours = &settings.colours;
+    let zoom = scale * options.block_size / BLOCK_SIZE;
+    let border = border_width(game.get_edge_policy());
+    let [width, height] = window_size(game.get_game_size(), border);
+    let window_settings =
+        WindowSettings::new(options.title.as_str(), [width as f64 * zoom, height as f64 * zoom])
+            .resizable(false)    
+            .exit_on_esc(false)
+            .vsync(options.vsync)
+            .samples(options.samples);
+
+    //Create window
+    let mut window: PistonWindow = window_settings.build().unwrap();
+    window.set_max_fps(options.max_fps);
+
+    let assets = find_folder::Search::ParentsThenKids(3, 3)
+        .for_folder("assets")
+        .unwrap();
+    let font = assets.join("retro-gaming.ttf");
+    let mut glyphs: Glyphs = window.load_font(font).unwrap();
+
+    let mut high_scores = HighScoreScreen::load();
+    let mut recorder = Recorder::new(&game);
+    let resume_hint = press_hint(&settings.keys.pause, "resume");
+    let restart_hint = press_hint(&settings.keys.restart, "restart");
+
+    while let Some(event) = window.next() {
+        //Stop the clock when the player switches to another window
+        if let Some(false) = event.focus_args() {
+            game.pause();
+        }
+        if let Some(Button::Keyboard(key)) = event.press_args() {
+            //Handle keypress, typing initials takes priority over the game
+            let action = settings.keys.action(key, game.is_over());
+            match frontend_action(action, &mut game, player.is_some(), autopilot.is_some()) {
+                Some(Action::Quit) => window.set_should_close(true),
+                Some(Action::Save) => save_game(&game),
+                Some(Action::Load) => {
+                    if let Some(loaded) = load_game(&game) {
+                        game = loaded;
+                        recorder = Recorder::new(&game);
+                        if let Some(autopilot) = autopilot.as_mut() {
+                            autopilot.reset(&game);
+                        }
+                    }
+                }
+                action => {
+                    if !high_scores.handle_keypress(key, &game) {
+                        if let Some(action) = action {
+                            recorder.apply(&mut game, action);
+                        }
+                    }
+                }
+            }
+        }
+
+        // Draw game
+        window.draw_2d(&event, |ctx, g, _| {
+            let mut renderer = PistonRenderer {
+                ctx: ctx.scale(zoom, zoom),
+                g,
+                glyphs: &mut glyphs,
+            };
+            game.draw(&mut renderer, colours, &resume_hint);
+            if game.is_over() && player.is_none() {
+                high_scores.draw(&mut renderer, colours, &restart_hint);
+            }
+        });
+
+        // Update the state of the game
+        event.update(|arg| {
+            match player.as_mut() {
+                Some(player) => player.feed(&mut game),
+                None => {
+                    //Runs played by the autopilot do not go into the high-score table
+                    match autopilot.as_mut() {
+                        Some(autopilot) => {
+                            if let Some(dir) = autopilot.next_move(&game) {
+                                recorder.apply(&mut game, Action::Move(dir));
+                            }
+                        }
+                        None => high_scores.watch(&game),
+                    }
+                    if let Some(Err(message)) = recorder.finished(&game).map(Replay::save_as_last) {
+                        eprintln!("{message}");
+                    }
+                }
+            }
+            game.update(arg.dt);
+        });
+    }
+}
+
+//The high-score table shown on the game-over screen, with the initials prompt for new records
+struct HighScoreScreen {
+    scores: HighScores,
+    path: Option<PathBuf>,
+    initials: Option<String>,
+    handled_game_over: bool,
+}
+
+impl HighScoreScreen {
+    fn load() -> Self {
+        let path = HighScores::default_path();
+        let scores = match &path {
+            Some(path) => HighScores::load(path).unwrap_or_else(|err| {
+                eprintln!("Could not read high scores from {}: {err}", path.display());
+                HighScores::default()
+            }),
+            None => HighScores::default(),
+        };
+        HighScoreScreen {
+            scores,
+            path,
+            initials: None,
+            handled_game_over: false,
+        }
+    }
+
+    //Starts the initials prompt the first time a game ends with a record score
+    fn watch(&mut self, game: &Game) {
+        if !game.is_over() {
+            self.handled_game_over = false;
+            return;
+        }
+        if !self.handled_game_over {
+            self.handled_game_over = true;
+            if self.scores.qualifies(game.get_points()) {
+                self.initials = Some(String::new());
+            }
+        }
+    }
+
+    //Returns true if the key was used for typing initials
+    fn handle_keypress(&mut self, key: Key, game: &Game) -> bool {
+        let Some(initials) = self.initials.as_mut() else {
+            return false;
+        };
+        match key {
+            Key::Backspace => {
+                initials.pop();
+            }
+            Key::Return if !initials.is_empty() => {
+                let entry = ScoreEntry::from_game(game, initials, &today());
+                self.initials = None;
+                self.scores.insert(entry);
+                self.save();
+            }
+            _ => {
+                let letter = char::from_u32(key.code() as u32).filter(char::is_ascii_alphanumeric);
+                if let Some(letter) = letter {
+                    if initials.len() < MAX_INITIALS {
+                        initials.push(letter.to_ascii_uppercase());
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    fn save(&self) {
+        let Some(path) = &self.path else { return };
+        if let Err(err) = self.scores.save(path) {
+            eprintln!("Could not save high scores to {}: {err}", path.display());
+        }
+    }
+
+    //Below the score of the game-over message
+    fn draw(&self, r: &mut impl Renderer, colours: &Colours, restart_hint: &str) {
+        let mut row = HUD_ROWS as f64 + 8.0;
+        for (rank, entry) in self.scores.get_entries().iter().enumerate() {
+            let line = format!(
+                "{:>2}. {:<3} {:>4}  {}x{} {} {}",
+                rank + 1,
+                entry.initials,
+                entry.score,
+                entry.board.0,
+                entry.board.1,
+                entry.mode,
+                entry.date
+            );
+            r.draw_text(colours.scores, 14, (1.0, row), &line);
+            row += 0.8;
+        }
+        row += 0.6;
+        match &self.initials {
+            Some(initials) => {
+                let prompt = format!("New record! Initials: {initials}_");
+                r.draw_text(colours.scores, 20, (1.0, row), &prompt);
+            }
+            None => r.draw_text(colours.scores, 20, (1.0, row), restart_hint),
+        }
+    }
+}
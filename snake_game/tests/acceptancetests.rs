@@ -1,14 +1,53 @@
 extern crate snake_game;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
 use std::str::FromStr;
 
+use clap::Parser;
+use crossterm::event::KeyCode;
 use cucumber::{given, then, when, World, Parameter};
 use snake_game::{Game, GameState};
-use snake_game::snake::{Snake, Direction};
+use snake_game::agent::{Agent, GameView};
+use snake_game::autopilot::{choose_direction, Autopilot, AutopilotMode};
+use snake_game::bindings::{Action, KeyBindings};
+use snake_game::clock::STEP_TIME;
+use snake_game::export::write_gif;
+use snake_game::external::ExternalBot;
+use snake_game::framebuffer::Framebuffer;
+use snake_game::hamilton::HamiltonCycle;
+use snake_game::highscore::{HighScores, ScoreEntry};
+use snake_game::level::Level;
+use snake_game::options::Options;
+use snake_game::render::{Cell, Color, Renderer};
+use snake_game::replay::{Recorder, Replay};
+use snake_game::settings::{Colours, Settings};
+use snake_game::snake::{Collision, Direction, EdgePolicy, Snake};
+use snake_game::speed::SpeedCurve;
+use snake_game::terminal::{frame_lines, key_from_terminal};
+use snake_game::tournament::{Ending, Report, Tournament, TournamentOptions};
 
 #[derive(World, Debug, Default, Clone)]
 pub struct State {
     input: Option<Game>,
     output: Option<Game>,
+    level_error: Option<String>,
+    options_error: Option<String>,
+    settings: Option<Settings>,
+    settings_error: Option<String>,
+    save_error: Option<String>,
+    replay: Option<Replay>,
+    loaded_replay: Option<Replay>,
+    recorder: Option<Recorder>,
+    replay_error: Option<String>,
+    high_scores: HighScores,
+    rank: Option<usize>,
+    drawn: DrawLog,
+    gif: Option<Vec<u8>>,
+    export_error: Option<String>,
+    tournament: Option<Tournament>,
+    reports: Vec<Report>,
+    bot_log: Option<String>,
 }
 
 #[derive(Debug, Default, Parameter)]
@@ -33,9 +72,9 @@ impl FromStr for CuKey {
         })
     }
 }
-impl Into<Direction> for CuKey {
-    fn into(self) -> Direction {
-        match self {
+impl From<CuKey> for Direction {
+    fn from(key: CuKey) -> Direction {
+        match key {
             CuKey::A => Direction::Left,
             CuKey::D => Direction::Right,
             CuKey::W => Direction::Up,
@@ -79,9 +118,9 @@ impl PartialEq<Direction> for CuDirection {
         }
     }
 }
-impl Into<Direction> for CuDirection {
-    fn into(self) -> Direction {
-        match self {
+impl From<CuDirection> for Direction {
+    fn from(dir: CuDirection) -> Direction {
+        match dir {
             CuDirection::Left => Direction::Left,
             CuDirection::Right => Direction::Right,
             CuDirection::Up => Direction::Up,
@@ -89,11 +128,113 @@ impl Into<Direction> for CuDirection {
         }
     }
 }
+#[derive(Debug, Default, Parameter)]
+#[param(name="edges", regex="wrap|solid")]
+enum CuEdges {
+    #[default]
+    Wrap,
+    Solid
+}
+impl FromStr for CuEdges {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "wrap" => Self::Wrap,
+            "solid" => Self::Solid,
+            invalid => return Err(format!("Invalid edges: {invalid}")),
+        })
+    }
+}
+impl From<CuEdges> for EdgePolicy {
+    fn from(edges: CuEdges) -> EdgePolicy {
+        match edges {
+            CuEdges::Wrap => EdgePolicy::Wrap,
+            CuEdges::Solid => EdgePolicy::Solid,
+        }
+    }
+}
+#[derive(Debug, Default, Parameter)]
+#[param(name="curve", regex="fixed|linear|stepped|custom")]
+enum CuCurve {
+    #[default]
+    Fixed,
+    Linear,
+    Stepped,
+    Custom
+}
+impl FromStr for CuCurve {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "fixed" => Self::Fixed,
+            "linear" => Self::Linear,
+            "stepped" => Self::Stepped,
+            "custom" => Self::Custom,
+            invalid => return Err(format!("Invalid speed curve: {invalid}")),
+        })
+    }
+}
+impl From<CuCurve> for SpeedCurve {
+    fn from(curve: CuCurve) -> SpeedCurve {
+        match curve {
+            CuCurve::Fixed => SpeedCurve::Fixed(0.2),
+            CuCurve::Linear => SpeedCurve::Linear { start: 0.2, per_point: 0.01, min: 0.1 },
+            CuCurve::Stepped => SpeedCurve::Stepped { start: 0.2, every: 5, decrease: 0.05, min: 0.1 },
+            CuCurve::Custom => SpeedCurve::Custom { table: vec![(0, 0.2), (3, 0.15), (10, 0.125)], min: 0.1 },
+        }
+    }
+}
+#[derive(Debug, Default, Parameter)]
+#[param(name="action", regex="move up|move down|move left|move right|pause|restart|quit|nothing")]
+enum CuAction {
+    #[default]
+    Nothing,
+    Move(Direction),
+    Pause,
+    Restart,
+    Quit
+}
+impl FromStr for CuAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "move up" => Self::Move(Direction::Up),
+            "move down" => Self::Move(Direction::Down),
+            "move left" => Self::Move(Direction::Left),
+            "move right" => Self::Move(Direction::Right),
+            "pause" => Self::Pause,
+            "restart" => Self::Restart,
+            "quit" => Self::Quit,
+            "nothing" => Self::Nothing,
+            invalid => return Err(format!("Invalid action: {invalid}")),
+        })
+    }
+}
+impl From<CuAction> for Option<Action> {
+    fn from(action: CuAction) -> Option<Action> {
+        match action {
+            CuAction::Nothing => None,
+            CuAction::Move(dir) => Some(Action::Move(dir)),
+            CuAction::Pause => Some(Action::Pause),
+            CuAction::Restart => Some(Action::Restart),
+            CuAction::Quit => Some(Action::Quit),
+        }
+    }
+}
 fn main() {
     futures::executor::block_on(State::run("features/"));
 }
 
 //Generic
+//Presses a key with the default key bindings
+fn press(game: &mut Game, key: piston_window::Key) {
+    if let Some(action) = KeyBindings::default().action(key, game.is_over()) {
+        game.handle_action(action);
+    }
+}
 #[given(expr = "we have a snake game")]
 fn given_snake(s: &mut State) {
     let (w, h) = (20,20);
@@ -104,8 +245,13 @@ fn given_snake(s: &mut State) {
         0.0,
         (w-1, h-1) //place it out of the way
         ,0
+        ,0
     ));
 }
+#[given(expr = "we have a snake game with seed {int}")]
+fn given_seeded_snake(s: &mut State, seed: u64) {
+    s.input = Some(Game::new(20, 20, seed));
+}
 #[when(expr = "the snake moves to a free spot")]
 fn when_free_spot(s: &mut State) {
     let input = s.input.to_owned().unwrap();
@@ -116,7 +262,8 @@ fn when_free_spot(s: &mut State) {
         GameState::Moving(Direction::Right),
         0.0,
         input.get_apple_loc(),
-        0
+        0,
+        input.get_seed()
     );
     output.update(1.0);
     s.output = Some(output);
@@ -132,7 +279,8 @@ fn when_apple_eaten(s: &mut State) {
         GameState::Moving(Direction::Right),
         0.0,
         (ax+1, ay),
-        0
+        0,
+        input.get_seed()
     );
     output.update(2.0); // eat the apple
     s.output = Some(output);
@@ -180,6 +328,7 @@ fn when_hit_self(s: &mut State) {
         0.0,
         input.get_apple_loc() //place it out of the way
         ,0
+        ,input.get_seed()
     );
     output.update_move_dir(Direction::Up);
     output.update(2.0);
@@ -189,12 +338,67 @@ fn when_hit_self(s: &mut State) {
     output.update(2.0);
     s.output = Some(output);
 }   
+#[when(expr = "the snake chases its own tail")]
+fn when_chase_tail(s: &mut State) {
+    let input = s.input.to_owned().unwrap();
+    let (w,h) = input.get_game_size();
+    let mut output = Game::new_constructed(
+        (w,h),
+        Snake::init_snake(4, Direction::Right, (w,h)),
+        input.get_state(),
+        0.0,
+        input.get_apple_loc(),
+        0,
+        input.get_seed()
+    );
+    //Up, left and down brings the head onto the cell the tail leaves on the same tick
+    for dir in [Direction::Up, Direction::Left, Direction::Down] {
+        output.update_move_dir(dir);
+        output.update(2.0);
+    }
+    s.output = Some(output);
+}
 #[then(expr = "it dies")]
 fn then_die(s: &mut State) {
     let output = s.output.to_owned().unwrap();
     assert_eq!(GameState::Dead, output.get_state(), "Snake did not die when expected to.");  
 }
 
+#[then(expr = "the board stays frozen")]
+fn then_board_frozen(s: &mut State) {
+    let ended = s.output.to_owned().unwrap();
+    let mut output = ended.clone();
+    for _ in 0..5 {
+        output.update(2.0);
+    }
+    assert_eq!(ended.get_state(), output.get_state(), "Game state changed after the game ended.");
+    assert_eq!(ended.get_snake().get_head_pos(), output.get_snake().get_head_pos(), "Snake moved after the game ended.");
+    assert_eq!(ended.get_apple_loc(), output.get_apple_loc(), "Apple moved after the game ended.");
+    assert_eq!(ended.get_points(), output.get_points(), "Points changed after the game ended.");
+}
+#[when(expr = "the {key} button is pressed after death")]
+fn when_key_press_after_death(s: &mut State, key: CuKey) {
+    let mut output = s.output.to_owned().unwrap();
+    output.update_move_dir(key.into());
+    output.update(2.0);
+    s.output = Some(output);
+}
+#[when(expr = "the restart button is pressed")]
+fn when_restart_pressed(s: &mut State) {
+    let mut output = s.output.to_owned().unwrap();
+    press(&mut output, piston_window::Key::R);
+    s.output = Some(output);
+}
+#[then(expr = "a new game starts on the same board")]
+fn then_new_game(s: &mut State) {
+    let input = s.input.to_owned().unwrap();
+    let output = s.output.to_owned().unwrap();
+    assert_eq!(GameState::Waiting, output.get_state(), "Game did not restart into the waiting state.");
+    assert_eq!(input.get_game_size(), output.get_game_size(), "Board size changed on restart.");
+    assert_eq!(0, output.get_points(), "Points were not reset on restart.");
+    assert_eq!(3, output.get_snake().get_length(), "Snake length was not reset on restart.");
+}
+
 //Movement
 #[when(expr = "the {key} button is pressed")]
 fn when_key_press(s: &mut State, key: CuKey) {
@@ -212,9 +416,10 @@ fn when_key_press(s: &mut State, key: CuKey) {
         GameState::Waiting,
         0.0,
         input.get_apple_loc(),
-        0
+        0,
+        input.get_seed()
     );
-    output.handle_keypress(keypress);
+    press(&mut output, keypress);
     output.update(2.0);
     s.output = Some(output);
 }
@@ -228,7 +433,7 @@ fn then_move_direction(s: &mut State, exp_dir: CuDirection) {
             assert_eq!(exp_dir, dir, "Snake did not move in the expected direction.");
             assert_ne!(input_snake_pos, output_snake_pos, "Snake position did not change when expected to.");
         }
-        _ => assert!(false, "Snake was not moving"),
+        _ => panic!("Snake was not moving"),
     }
 } 
 
@@ -242,7 +447,8 @@ fn when_reverse_direction(s: &mut State, org_dir: CuDirection, new_dir: CuDirect
         GameState::Waiting,
         0.0,
         input.get_apple_loc(),
-        0
+        0,
+        input.get_seed()
     );
     //Move once to the org_dir
     output.update_move_dir(org_dir.into());
@@ -253,6 +459,34 @@ fn when_reverse_direction(s: &mut State, org_dir: CuDirection, new_dir: CuDirect
     s.output = Some(output);
 }
 
+fn parse_directions(list: &str) -> Vec<CuDirection> {
+    list.split_whitespace().map(|dir| dir.parse().unwrap()).collect()
+}
+#[when(expr = "the snake is moving right and we quickly tap {string}")]
+fn when_quick_turns(s: &mut State, taps: String) {
+    let mut output = moving_right_game(s, 0.0);
+    for dir in parse_directions(&taps) {
+        output.update_move_dir(dir.into());
+    }
+    s.output = Some(output);
+}
+#[then(expr = "over the next ticks the snake moves {string}")]
+fn then_moves_over_ticks(s: &mut State, moves: String) {
+    let mut output = s.output.to_owned().unwrap();
+    for exp_dir in parse_directions(&moves) {
+        let (org_x, org_y) = output.get_snake().get_head_pos();
+        output.step();
+        let (new_x, new_y) = output.get_snake().get_head_pos();
+        let delta = match exp_dir {
+            CuDirection::Up => (0, -1),
+            CuDirection::Down => (0, 1),
+            CuDirection::Left => (-1, 0),
+            CuDirection::Right => (1, 0),
+        };
+        assert_eq!((org_x + delta.0, org_y + delta.1), (new_x, new_y), "Snake did not move {:?} on this tick.", exp_dir);
+    }
+}
+
 //Apple Spawning
 #[then(expr = "an apple should be spawned")]
 fn then_spawn_apple(s: &mut State) {
@@ -264,6 +498,46 @@ fn then_spawn_apple(s: &mut State) {
     assert_ne!(org_apple_loc, new_apple_loc, "Apple location was expected to change, but it did not.");
 } 
 
+#[then(expr = "another game with seed {int} places the apple in the same spot")]
+fn then_same_seed_same_apple(s: &mut State, seed: u64) {
+    let input = s.input.to_owned().unwrap();
+    let (w, h) = input.get_game_size();
+    let other = Game::new(w, h, seed);
+    assert_eq!(input.get_apple_loc(), other.get_apple_loc(), "Same seed placed the apple differently.");
+}
+#[then(expr = "the apple starts at {int},{int}")]
+fn then_apple_starts_at(s: &mut State, x: i32, y: i32) {
+    let input = s.input.to_owned().unwrap();
+    assert_eq!((x, y), input.get_apple_loc(), "Apple did not start at the expected location.");
+}
+#[then(expr = "another game with seed {int} places the same {int} apples in a row")]
+fn then_same_seed_same_apples(s: &mut State, seed: u64, count: usize) {
+    let input = s.input.to_owned().unwrap();
+    let (w, h) = input.get_game_size();
+    let apples = apple_sequence(input, count);
+    assert_eq!(count, apples.len(), "Game ended before eating {count} apples.");
+    assert_eq!(apples, apple_sequence(Game::new(w, h, seed), count), "Same seed placed the apples differently.");
+}
+#[then(expr = "a game with seed {int} places its first {int} apples differently")]
+fn then_other_seed_other_apples(s: &mut State, seed: u64, count: usize) {
+    let input = s.input.to_owned().unwrap();
+    let (w, h) = input.get_game_size();
+    assert_ne!(apple_sequence(input, count), apple_sequence(Game::new(w, h, seed), count), "Different seeds placed the same apples.");
+}
+//Where the first `count` apples appear while the autopilot eats them one after the other
+fn apple_sequence(mut game: Game, count: usize) -> Vec<(i32, i32)> {
+    let mut autopilot = Autopilot::new();
+    let mut apples = vec![game.get_apple_loc()];
+    while apples.len() < count && !game.is_over() {
+        let (points, ticks) = (game.get_points(), game.get_ticks());
+        autopilot.play(&mut game, ticks + 1);
+        if game.get_points() > points {
+            apples.push(game.get_apple_loc());
+        }
+    }
+    apples
+}
+
 #[when(expr = "the snake moves over the edge")]
 fn when_leave_edge(s: &mut State) {
     let input = s.input.to_owned().unwrap();
@@ -274,7 +548,8 @@ fn when_leave_edge(s: &mut State) {
         GameState::Waiting,
         0.0,
         input.get_apple_loc(),
-        0
+        0,
+        input.get_seed()
     );
     output.update_move_dir(Direction::Left);
     output.update(2.0);
@@ -301,4 +576,1164 @@ fn then_point(s: &mut State) {
     assert_ne!(org_points, new_points, "Points did not change when expected to.");
     assert!(new_points > org_points, "Points did not increase when expected to.");
     assert_eq!(new_points, org_points+1, "Points increased by more than 1.");
-}
\ No newline at end of file
+}
+
+//Timing
+fn moving_right_game(s: &State, interval: f64) -> Game {
+    let input = s.input.to_owned().unwrap();
+    Game::new_constructed(
+        input.get_game_size(),
+        input.get_snake(),
+        GameState::Moving(Direction::Right),
+        interval,
+        input.get_apple_loc(),
+        0,
+        input.get_seed()
+    )
+}
+#[when(expr = "the snake is stepped {int} times while moving right")]
+fn when_stepped(s: &mut State, steps: u32) {
+    let mut output = moving_right_game(s, 0.0);
+    for _ in 0..steps {
+        output.step();
+    }
+    s.output = Some(output);
+}
+#[when(expr = "a frame shorter than a tick passes while moving right")]
+fn when_short_frame(s: &mut State) {
+    let mut output = moving_right_game(s, STEP_TIME);
+    output.update(STEP_TIME / 2.0);
+    s.output = Some(output);
+}
+#[when(expr = "a frame of {int} seconds passes while moving right")]
+fn when_long_frame(s: &mut State, seconds: u32) {
+    let mut output = moving_right_game(s, 0.0);
+    output.update(seconds as f64);
+    s.output = Some(output);
+}
+#[when(expr = "{int} frames of {float} seconds pass while waiting")]
+fn when_frames_waiting(s: &mut State, frames: u32, seconds: f64) {
+    let mut output = s.output.to_owned().unwrap_or(s.input.to_owned().unwrap());
+    for _ in 0..frames {
+        output.update(seconds);
+    }
+    s.output = Some(output);
+}
+#[when(expr = "{int} frames of {float} seconds pass while moving right")]
+fn when_frames_moving(s: &mut State, frames: u32, seconds: f64) {
+    let mut output = s.output.to_owned().unwrap_or(s.input.to_owned().unwrap());
+    output.update_move_dir(Direction::Right);
+    for _ in 0..frames {
+        output.update(seconds);
+    }
+    s.output = Some(output);
+}
+#[then(expr = "{float} seconds have elapsed")]
+fn then_elapsed(s: &mut State, seconds: f64) {
+    let output = s.output.to_owned().unwrap();
+    assert!((output.get_elapsed() - seconds).abs() < 1e-9, "Elapsed time was {} instead of {seconds}.", output.get_elapsed());
+}
+#[then(expr = "the tick speed is {float} ticks per second")]
+fn then_tick_speed(s: &mut State, speed: f64) {
+    let game = latest_game(s);
+    assert!((game.get_ticks_per_second() - speed).abs() < 1e-9, "Tick speed was {} instead of {speed}.", game.get_ticks_per_second());
+}
+#[then(expr = "the snake head has moved {int} cells to the right")]
+fn then_moved_right(s: &mut State, cells: i32) {
+    let (org_x, org_y) = s.input.to_owned().unwrap().get_snake().get_head_pos();
+    let (new_x, new_y) = s.output.to_owned().unwrap().get_snake().get_head_pos();
+    assert_eq!((org_x + cells, org_y), (new_x, new_y), "Snake did not move the expected number of cells.");
+}
+#[then(expr = "{int} ticks have passed")]
+fn then_ticks_passed(s: &mut State, ticks: u64) {
+    let output = s.output.to_owned().unwrap();
+    assert_eq!(ticks, output.get_ticks(), "Unexpected number of ticks.");
+}
+
+//Speed curves
+#[then(expr = "with {int} points the {curve} speed curve ticks {float} times per second")]
+fn then_curve_speed(_s: &mut State, points: i32, curve: CuCurve, speed: f64) {
+    let step_time = SpeedCurve::from(curve).step_time(points);
+    assert!((1.0 / step_time - speed).abs() < 1e-9, "Tick speed was {} instead of {speed}.", 1.0 / step_time);
+}
+#[given(expr = "we have a snake game with a {curve} speed curve")]
+fn given_curve_game(s: &mut State, curve: CuCurve) {
+    given_snake(s);
+    let mut input = s.input.to_owned().unwrap();
+    input.set_speed_curve(curve.into());
+    s.input = Some(input);
+}
+#[when(expr = "the snake eats an apple reaching {int} points")]
+fn when_eats_with_points(s: &mut State, points: i32) {
+    let input = s.input.to_owned().unwrap();
+    let mut output = Game::new_constructed(
+        input.get_game_size(),
+        input.get_snake(),
+        GameState::AteApple,
+        0.0,
+        input.get_apple_loc(),
+        points - 1,
+        input.get_seed()
+    );
+    output.set_speed_curve(input.get_speed_curve().clone());
+    output.step();
+    s.output = Some(output);
+}
+#[when(expr = "the game is restarted")]
+fn when_restarted(s: &mut State) {
+    let mut output = latest_game(s);
+    output.restart();
+    s.output = Some(output);
+}
+#[then(expr = "the game uses a {curve} speed curve")]
+fn then_uses_curve(s: &mut State, curve: CuCurve) {
+    assert_eq!(&SpeedCurve::from(curve), latest_game(s).get_speed_curve(), "Unexpected speed curve.");
+}
+
+//Winning
+#[given(expr = "we have a {int} by {int} snake game with one free cell")]
+fn given_almost_full_board(s: &mut State, w: i32, h: i32) {
+    //The snake fills the row except for the last cell, where the apple is
+    assert_eq!(1, h, "Only single row boards are supported by this step.");
+    given_long_snake(s, w, h);
+}
+#[given(expr = "we have a {int} by {int} snake game with a long snake")]
+fn given_long_snake(s: &mut State, w: i32, h: i32) {
+    s.input = Some(Game::new_constructed(
+        (w, h),
+        Snake::new(w - 2, 0, w - 1, Direction::Right, (w, h)),
+        GameState::Waiting,
+        0.0,
+        (w - 1, 0),
+        0,
+        0
+    ));
+}
+#[then(expr = "the game is won")]
+fn then_won(s: &mut State) {
+    let output = s.output.to_owned().unwrap();
+    assert_eq!(GameState::Won, output.get_state(), "Game was not won when the board filled up.");
+    let (w, h) = output.get_game_size();
+    assert_eq!((w * h) as usize, output.get_snake().get_length(), "Snake does not fill the board.");
+}
+#[then(expr = "for every seed from {int} to {int} the next apple spawns on a free cell")]
+fn then_apple_on_free_cell(s: &mut State, from: u64, to: u64) {
+    let input = s.input.to_owned().unwrap();
+    for seed in from..=to {
+        let mut output = Game::new_constructed(
+            input.get_game_size(),
+            input.get_snake(),
+            GameState::Moving(Direction::Right),
+            0.0,
+            input.get_apple_loc(),
+            0,
+            seed
+        );
+        output.update(2.0); // eat the apple
+        output.update(2.0); // spawn the next one
+        let apple = output.get_apple_loc();
+        let snake = output.get_snake();
+        assert!(snake.get_body().iter().all(|block| *block != apple), "Apple spawned on the snake with seed {seed}.");
+    }
+}
+
+//Edges
+#[given(expr = "we have a {int} by {int} snake game with {edges} edges")]
+fn given_sized_snake(s: &mut State, w: i32, h: i32, edges: CuEdges) {
+    let mut input = Game::new_constructed(
+        (w, h),
+        Snake::init_snake(3, Direction::Right, (w, h)),
+        GameState::Waiting,
+        0.0,
+        (w - 1, h - 1),
+        0,
+        0
+    );
+    input.set_edge_policy(edges.into());
+    s.input = Some(input);
+}
+#[when(expr = "the snake runs {dir} from {int},{int}")]
+fn when_run_from(s: &mut State, dir: CuDirection, x: i32, y: i32) {
+    let input = s.input.to_owned().unwrap();
+    let dir: Direction = dir.into();
+    let mut output = Game::new_constructed(
+        input.get_game_size(),
+        Snake::new(x, y, 1, dir.clone(), input.get_game_size()),
+        GameState::Waiting,
+        0.0,
+        input.get_apple_loc(),
+        0,
+        input.get_seed()
+    );
+    output.set_edge_policy(input.get_edge_policy());
+    output.update_move_dir(dir);
+    output.update(2.0);
+    s.output = Some(output);
+}
+#[then(expr = "the snake head is at {int},{int}")]
+fn then_head_at(s: &mut State, x: i32, y: i32) {
+    let output = s.output.to_owned().unwrap();
+    assert_eq!((x, y), output.get_snake().get_head_pos(), "Snake head is not where it was expected.");
+}
+#[then(expr = "it dies from hitting a wall")]
+fn then_die_wall(s: &mut State) {
+    let output = s.output.to_owned().unwrap();
+    assert_eq!(GameState::Dead, output.get_state(), "Snake did not die when expected to.");
+    assert_eq!(Some(Collision::Wall), output.get_death_cause(), "Snake did not die from a wall.");
+}
+
+//Levels
+//Inline levels separate their rows with '/', anything ending in .txt is loaded from disk
+fn parse_level(level: &str) -> Result<Level, String> {
+    let result = if level.ends_with(".txt") {
+        Level::load(Path::new(level))
+    } else {
+        Level::parse("inline", &level.replace('/', "\n"))
+    };
+    result.map_err(|err| err.to_string())
+}
+fn latest_game(s: &State) -> Game {
+    s.output.to_owned().or(s.input.to_owned()).unwrap()
+}
+#[given(expr = "we have the level {string}")]
+fn given_level(s: &mut State, level: String) {
+    let level = parse_level(&level).unwrap();
+    s.input = Some(Game::from_level(&level, 0));
+}
+#[when(expr = "we parse the level {string}")]
+fn when_parse_level(s: &mut State, level: String) {
+    s.level_error = parse_level(&level).err();
+}
+#[when(expr = "we parse a level of {int} by {int} tiles")]
+fn when_parse_large_level(s: &mut State, w: usize, h: usize) {
+    let mut rows = vec![".".repeat(w); h];
+    rows[0].replace_range(w - 1.., ">");
+    s.level_error = Level::parse("large", &rows.join("\n")).err().map(|err| err.to_string());
+}
+#[when(expr = "the level is played for {int} ticks")]
+fn when_level_played(s: &mut State, ticks: u32) {
+    let mut output = s.input.to_owned().unwrap();
+    output.update_move_dir(output.get_snake().prev_dir);
+    for _ in 0..ticks {
+        output.step();
+    }
+    s.output = Some(output);
+}
+#[then(expr = "the board is {int} by {int}")]
+fn then_board_size(s: &mut State, w: i32, h: i32) {
+    assert_eq!((w, h), latest_game(s).get_game_size(), "Board has the wrong size.");
+}
+#[then(expr = "the level has {int} obstacles")]
+fn then_obstacle_count(s: &mut State, count: usize) {
+    let game = latest_game(s);
+    assert_eq!(count, game.get_board().get_obstacles().len(), "Level has the wrong number of obstacles.");
+}
+#[then(expr = "the snake starts at {int},{int} heading {dir}")]
+fn then_snake_start(s: &mut State, x: i32, y: i32, dir: CuDirection) {
+    let game = latest_game(s);
+    assert_eq!(GameState::Waiting, game.get_state(), "Game is not waiting to start.");
+    assert_eq!((x, y), game.get_snake().get_head_pos(), "Snake does not start at the expected location.");
+    assert_eq!(dir, game.get_snake().prev_dir, "Snake does not start with the expected heading.");
+}
+#[then(expr = "it dies from hitting an obstacle")]
+fn then_die_obstacle(s: &mut State) {
+    let output = s.output.to_owned().unwrap();
+    assert_eq!(GameState::Dead, output.get_state(), "Snake did not die when expected to.");
+    assert_eq!(Some(Collision::Obstacle), output.get_death_cause(), "Snake did not die from an obstacle.");
+}
+#[then(expr = "for every seed from {int} to {int} the first apple spawns on a free cell")]
+fn then_first_apple_free(s: &mut State, from: u64, to: u64) {
+    let input = s.input.to_owned().unwrap();
+    let level = input.get_level().unwrap();
+    for seed in from..=to {
+        let game = Game::from_level(level, seed);
+        let apple = game.get_apple_loc();
+        assert!(!game.get_board().is_obstacle(apple), "Apple spawned on an obstacle with seed {seed}.");
+        assert!(game.get_snake().get_body().iter().all(|block| *block != apple), "Apple spawned on the snake with seed {seed}.");
+    }
+}
+#[then(expr = "the level is rejected with {string}")]
+fn then_level_rejected(s: &mut State, error: String) {
+    let level_error = s.level_error.to_owned().expect("Level was accepted unexpectedly.");
+    assert!(level_error.contains(&error), "Unexpected level error: {level_error}");
+}
+
+//High scores
+fn parse_scores(scores: &str) -> Vec<i32> {
+    scores.split_whitespace().map(|score| score.parse().unwrap()).collect()
+}
+fn score_entry(score: i32) -> ScoreEntry {
+    ScoreEntry {
+        initials: "AAA".to_string(),
+        score,
+        length: 3 + score as usize,
+        board: (20, 20),
+        date: "2024-01-01".to_string(),
+        mode: "wrap".to_string(),
+    }
+}
+#[given(expr = "we have a high-score table with the scores {string}")]
+fn given_high_scores(s: &mut State, scores: String) {
+    s.high_scores = HighScores::default();
+    for score in parse_scores(&scores) {
+        s.high_scores.insert(score_entry(score));
+    }
+}
+#[when(expr = "a run scoring {int} is recorded")]
+fn when_run_recorded(s: &mut State, score: i32) {
+    s.rank = s.high_scores.insert(score_entry(score));
+}
+#[when(expr = "the game is recorded for {string}")]
+fn when_game_recorded(s: &mut State, initials: String) {
+    let input = s.input.to_owned().unwrap();
+    s.rank = s.high_scores.insert(ScoreEntry::from_game(&input, &initials, "2024-01-01"));
+}
+#[then(expr = "the run is ranked {int}")]
+fn then_run_ranked(s: &mut State, rank: usize) {
+    assert_eq!(Some(rank - 1), s.rank, "Run got the wrong rank.");
+}
+#[then(expr = "the table lists the scores {string}")]
+fn then_table_lists(s: &mut State, scores: String) {
+    let listed: Vec<i32> = s.high_scores.get_entries().iter().map(|entry| entry.score).collect();
+    assert_eq!(parse_scores(&scores), listed, "Table lists the wrong scores.");
+}
+#[then(expr = "a score of {int} qualifies")]
+fn then_score_qualifies(s: &mut State, score: i32) {
+    assert!(s.high_scores.qualifies(score), "Score {score} did not qualify.");
+}
+#[then(expr = "a score of {int} does not qualify")]
+fn then_score_does_not_qualify(s: &mut State, score: i32) {
+    assert!(!s.high_scores.qualifies(score), "Score {score} qualified unexpectedly.");
+}
+#[then(expr = "the table is the same after saving and loading it")]
+fn then_table_round_trips(s: &mut State) {
+    let path = std::env::temp_dir().join(format!("snake_game_scores_{}.txt", std::process::id()));
+    s.high_scores.save(&path).unwrap();
+    let loaded = HighScores::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(s.high_scores, loaded, "Table changed after saving and loading.");
+}
+#[then(expr = "the recorded run is {string} on a {int}x{int} {string} board with length {int}")]
+fn then_recorded_run(s: &mut State, initials: String, w: i32, h: i32, mode: String, length: usize) {
+    let entry = &s.high_scores.get_entries()[s.rank.unwrap()];
+    assert_eq!(initials, entry.initials, "Recorded run has the wrong initials.");
+    assert_eq!((w, h), entry.board, "Recorded run has the wrong board size.");
+    assert_eq!(mode, entry.mode, "Recorded run has the wrong mode.");
+    assert_eq!(length, entry.length, "Recorded run has the wrong length.");
+}
+
+//Pausing
+#[when(expr = "the snake is moving right and the P button is pressed")]
+fn when_pause_moving(s: &mut State) {
+    let mut output = moving_right_game(s, STEP_TIME);
+    press(&mut output, piston_window::Key::P);
+    s.output = Some(output);
+}
+#[when(expr = "{int} frames of {float} seconds pass while paused")]
+fn when_frames_paused(s: &mut State, frames: u32, seconds: f64) {
+    let mut output = s.output.to_owned().unwrap();
+    for _ in 0..frames {
+        output.update(seconds);
+    }
+    s.output = Some(output);
+}
+#[when(expr = "the P button is pressed again")]
+fn when_pause_again(s: &mut State) {
+    let mut output = s.output.to_owned().unwrap();
+    press(&mut output, piston_window::Key::P);
+    s.output = Some(output);
+}
+#[when(expr = "we tap {dir} while paused")]
+fn when_tap_paused(s: &mut State, dir: CuDirection) {
+    let mut output = s.output.to_owned().unwrap();
+    output.update_move_dir(dir.into());
+    s.output = Some(output);
+}
+#[when(expr = "the P button is pressed while waiting")]
+fn when_pause_waiting(s: &mut State) {
+    let mut output = s.input.to_owned().unwrap();
+    press(&mut output, piston_window::Key::P);
+    s.output = Some(output);
+}
+#[then(expr = "the game is paused while moving {dir}")]
+fn then_paused(s: &mut State, dir: CuDirection) {
+    match s.output.to_owned().unwrap().get_state() {
+        GameState::Paused(paused_dir) => assert_eq!(dir, paused_dir, "Pause did not remember the direction."),
+        state => panic!("Game was not paused: {state:?}"),
+    }
+}
+#[then(expr = "the snake is moving {dir}")]
+fn then_moving(s: &mut State, dir: CuDirection) {
+    match s.output.to_owned().unwrap().get_state() {
+        GameState::Moving(moving_dir) => assert_eq!(dir, moving_dir, "Snake is moving in the wrong direction."),
+        state => panic!("Snake was not moving: {state:?}"),
+    }
+}
+#[then(expr = "the clock has not moved")]
+fn then_clock_frozen(s: &mut State) {
+    let output = s.output.to_owned().unwrap();
+    assert_eq!(STEP_TIME, output.get_clock().get_interval(), "Clock kept running while paused.");
+    assert_eq!(0.0, output.get_elapsed(), "Elapsed time kept counting while paused.");
+    assert_eq!(0, output.get_ticks(), "Ticks passed while paused.");
+}
+#[then(expr = "the snake head has moved {int} cells to the right after a tick")]
+fn then_moved_right_after_tick(s: &mut State, cells: i32) {
+    let mut output = s.output.to_owned().unwrap();
+    output.update(STEP_TIME);
+    s.output = Some(output);
+    then_moved_right(s, cells);
+}
+#[then(expr = "the game is still waiting")]
+fn then_still_waiting(s: &mut State) {
+    assert_eq!(GameState::Waiting, s.output.to_owned().unwrap().get_state(), "Game left the waiting state.");
+}
+
+//Command-line options
+#[when(expr = "the game is started with {string}")]
+fn when_started_with(s: &mut State, args: String) {
+    let args = std::iter::once("snake_game").chain(args.split_whitespace());
+    let game = Options::try_parse_from(args)
+        .map_err(|err| err.to_string())
+        .and_then(|options| {
+            //Only read a settings file when one is given, never the user's own
+            let settings = match options.settings {
+                Some(_) => options.load_settings().map_err(|err| err.to_string())?,
+                None => s.settings.to_owned().unwrap_or_default(),
+            };
+            let game = options.build_game(&settings).map_err(|err| err.to_string())?;
+            options.build_autopilot(&game).map_err(|err| err.to_string())?;
+            Ok(game)
+        });
+    match game {
+        Ok(game) => s.input = Some(game),
+        Err(err) => s.options_error = Some(err),
+    }
+}
+#[then(expr = "the snake is {int} long")]
+fn then_snake_length(s: &mut State, length: usize) {
+    assert_eq!(length, latest_game(s).get_snake().get_length(), "Snake has the wrong length.");
+}
+#[then(expr = "the edges are {edges}")]
+fn then_edges(s: &mut State, edges: CuEdges) {
+    assert_eq!(EdgePolicy::from(edges), latest_game(s).get_edge_policy(), "Game has the wrong edges.");
+}
+#[then(expr = "the game uses seed {int}")]
+fn then_seed(s: &mut State, seed: u64) {
+    assert_eq!(seed, latest_game(s).get_seed(), "Game has the wrong seed.");
+}
+#[then(expr = "starting fails with {string}")]
+fn then_start_fails(s: &mut State, error: String) {
+    let options_error = s.options_error.to_owned().expect("Options were accepted unexpectedly.");
+    assert!(options_error.contains(&error), "Unexpected options error: {options_error}");
+}
+
+//Key bindings
+#[given(expr = "the default settings")]
+fn given_default_settings(s: &mut State) {
+    s.settings = Some(Settings::default());
+}
+#[when(expr = "we load the settings {string}")]
+fn when_load_settings(s: &mut State, text: String) {
+    match Settings::parse(&text) {
+        Ok(settings) => s.settings = Some(settings),
+        Err(err) => s.settings_error = Some(err.to_string()),
+    }
+}
+fn assert_key_means(s: &State, key: String, action: CuAction, over: bool) {
+    let settings = s.settings.to_owned().expect("Settings were rejected unexpectedly.");
+    let key: piston_window::Key = toml::Value::String(key).try_into().unwrap();
+    assert_eq!(Option::<Action>::from(action), settings.keys.action(key, over), "Key {key:?} is bound to the wrong action.");
+}
+#[then(expr = "the {word} key means {action} while playing")]
+fn then_key_while_playing(s: &mut State, key: String, action: CuAction) {
+    assert_key_means(s, key, action, false);
+}
+#[then(expr = "the {word} key means {action} when the game is over")]
+fn then_key_when_over(s: &mut State, key: String, action: CuAction) {
+    assert_key_means(s, key, action, true);
+}
+#[then(expr = "the settings are the defaults")]
+fn then_default_settings(s: &mut State) {
+    assert_eq!(Some(Settings::default()), s.settings, "Settings differ from the defaults.");
+}
+#[then(expr = "the settings are rejected with {string}")]
+fn then_settings_rejected(s: &mut State, error: String) {
+    let settings_error = s.settings_error.to_owned().expect("Settings were accepted unexpectedly.");
+    assert!(settings_error.contains(&error), "Unexpected settings error: {settings_error}");
+}
+
+//Settings
+#[when(expr = "we load the settings file {string}")]
+fn when_load_settings_file(s: &mut State, path: String) {
+    match Settings::load(Path::new(&path)) {
+        Ok(settings) => s.settings = Some(settings),
+        Err(err) => s.settings_error = Some(err.to_string()),
+    }
+}
+#[then(expr = "the apple colour is {float},{float},{float},{float}")]
+fn then_apple_colour(s: &mut State, r: f32, g: f32, b: f32, a: f32) {
+    let settings = s.settings.to_owned().expect("Settings were rejected unexpectedly.");
+    assert_eq!([r, g, b, a], settings.colours.apple, "Apple has the wrong colour.");
+}
+#[then(expr = "the snake colour is the default")]
+fn then_default_snake_colour(s: &mut State) {
+    let settings = s.settings.to_owned().expect("Settings were rejected unexpectedly.");
+    assert_eq!(Settings::default().colours.snake, settings.colours.snake, "Snake colour was not kept.");
+}
+#[then(expr = "the key bindings are the defaults")]
+fn then_default_bindings(s: &mut State) {
+    let settings = s.settings.to_owned().expect("Settings were rejected unexpectedly.");
+    assert_eq!(KeyBindings::default(), settings.keys, "Key bindings were not kept.");
+}
+
+//Saving and loading
+#[when(expr = "the game is played for {int} ticks with turns {string}")]
+fn when_played_with_turns(s: &mut State, ticks: u32, turns: String) {
+    let mut output = s.input.to_owned().unwrap();
+    let turns: Vec<Direction> = parse_directions(&turns).into_iter().map(Direction::from).collect();
+    for tick in 0..ticks {
+        if tick % 5 == 0 {
+            output.update_move_dir(turns[tick as usize / 5 % turns.len()].clone());
+        }
+        output.step();
+    }
+    s.input = Some(output);
+}
+#[when(expr = "the snake starts moving and turns {string} are queued mid-tick")]
+fn when_turns_queued(s: &mut State, turns: String) {
+    let mut input = s.input.to_owned().unwrap();
+    input.update_move_dir(Direction::Right);
+    input.update(STEP_TIME);
+    input.update(STEP_TIME / 3.0);
+    for dir in parse_directions(&turns) {
+        input.update_move_dir(dir.into());
+    }
+    s.input = Some(input);
+}
+#[when(expr = "the game is saved and loaded")]
+fn when_saved_and_loaded(s: &mut State) {
+    let input = s.input.to_owned().unwrap();
+    s.output = Some(Game::from_save(&input.to_save()).unwrap());
+}
+#[when(expr = "the game is saved to a file and loaded")]
+fn when_saved_to_file(s: &mut State) {
+    let input = s.input.to_owned().unwrap();
+    let path = std::env::temp_dir().join(format!("snake_game_test_{}", std::process::id())).join("save.json");
+    input.save(&path).unwrap();
+    s.output = Some(Game::load(&path).unwrap());
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+#[when(expr = "the game is saved with its {word} set to {string} and loaded")]
+fn when_saved_corrupted(s: &mut State, field: String, value: String) {
+    let input = s.input.to_owned().unwrap();
+    let mut json: serde_json::Value = serde_json::from_str(&input.to_save()).unwrap();
+    json["game"][field] = serde_json::from_str(&value).unwrap();
+    match Game::from_save(&json.to_string()) {
+        Ok(game) => s.output = Some(game),
+        Err(err) => s.save_error = Some(err.to_string()),
+    }
+}
+#[when(expr = "the game is deserialized on its own")]
+fn when_deserialized(s: &mut State) {
+    let input = s.input.to_owned().unwrap();
+    let json = serde_json::to_string(&input).unwrap();
+    s.output = Some(serde_json::from_str::<Game>(&json).unwrap());
+}
+#[when(expr = "we load the save {string}")]
+fn when_load_save(s: &mut State, save: String) {
+    s.save_error = Game::from_save(&save).err().map(|err| err.to_string());
+}
+#[then(expr = "the loaded game matches the saved one")]
+fn then_loaded_matches(s: &mut State) {
+    let input = s.input.to_owned().unwrap();
+    let output = s.output.to_owned().unwrap();
+    assert_eq!(input.to_save(), output.to_save(), "Loaded game differs from the saved one.");
+}
+#[then(expr = "both games stay identical for {int} more ticks")]
+fn then_stay_identical(s: &mut State, ticks: u32) {
+    let mut input = s.input.to_owned().unwrap();
+    let mut output = s.output.to_owned().unwrap();
+    for tick in 0..ticks {
+        if tick % 7 == 0 {
+            let dir = [Direction::Up, Direction::Left, Direction::Down, Direction::Right][tick as usize / 7 % 4].clone();
+            input.update_move_dir(dir.clone());
+            output.update_move_dir(dir);
+        }
+        input.update(STEP_TIME);
+        output.update(STEP_TIME);
+        assert_eq!(input.to_save(), output.to_save(), "Games diverged after {tick} ticks.");
+    }
+}
+#[then(expr = "the loaded game has {int} queued turns")]
+fn then_loaded_queue(s: &mut State, turns: usize) {
+    let input = s.input.to_owned().unwrap();
+    let output = s.output.to_owned().unwrap();
+    assert_eq!(turns, output.get_queued_turns().len(), "Queued turns were not kept.");
+    assert_eq!(input.get_queued_turns(), output.get_queued_turns(), "Queued turns differ.");
+}
+#[then(expr = "the loaded clock interval matches the saved one")]
+fn then_loaded_clock(s: &mut State) {
+    let input = s.input.to_owned().unwrap();
+    let output = s.output.to_owned().unwrap();
+    assert_eq!(input.get_clock().get_interval(), output.get_clock().get_interval(), "Clock interval was not kept.");
+    assert_eq!(input.get_clock().get_step_time(), output.get_clock().get_step_time(), "Step time was not kept.");
+}
+#[then(expr = "the save is rejected with {string}")]
+fn then_save_rejected(s: &mut State, error: String) {
+    let save_error = s.save_error.to_owned().expect("Save was accepted unexpectedly.");
+    assert!(save_error.contains(&error), "Unexpected save error: {save_error}");
+}
+
+//Replays
+#[given(expr = "we have a {int} by {int} snake game with {edges} edges and seed {int}")]
+fn given_seeded_sized_snake(s: &mut State, w: i32, h: i32, edges: CuEdges, seed: u64) {
+    let mut input = Game::new(w, h, seed);
+    input.set_edge_policy(edges.into());
+    s.input = Some(input);
+}
+#[when(expr = "the game is recorded while turning {string} every {int} ticks until it ends")]
+fn when_recorded(s: &mut State, turns: String, every: u64) {
+    let turns: Vec<Direction> = parse_directions(&turns).into_iter().map(Direction::from).collect();
+    let mut game = s.input.to_owned().unwrap();
+    let mut recorder = Recorder::new(&game);
+    let mut pressed = 0;
+    let mut last_tick = None;
+    while !game.is_over() && game.get_ticks() < 5000 {
+        //Turn once on every `every`th tick, in the middle of a tick so the clock is mid-way
+        if game.get_ticks().is_multiple_of(every) && last_tick != Some(game.get_ticks()) {
+            last_tick = Some(game.get_ticks());
+            recorder.apply(&mut game, Action::Move(turns[pressed % turns.len()].clone()));
+            pressed += 1;
+        }
+        game.update(STEP_TIME / 2.0);
+    }
+    s.replay = Some(recorder.finished(&game).expect("Game did not end.").clone());
+    s.output = Some(game);
+}
+#[when(expr = "the game is recorded while chasing the apple until it ends")]
+fn when_recorded_chasing(s: &mut State) {
+    let mut game = s.input.to_owned().unwrap();
+    let mut recorder = Recorder::new(&game);
+    while !game.is_over() && game.get_ticks() < 5000 {
+        //Head for the apple, first along x then along y, never straight back
+        let (x, y) = game.get_snake().get_head_pos();
+        let (ax, ay) = game.get_apple_loc();
+        let heading = game.get_snake().prev_dir;
+        let wanted = [
+            (ax > x, Direction::Right),
+            (ax < x, Direction::Left),
+            (ay > y, Direction::Down),
+            (ay < y, Direction::Up),
+        ];
+        let dir = wanted.into_iter().find(|(want, dir)| *want && *dir != opposite(&heading)).map(|(_, dir)| dir);
+        if let Some(dir) = dir {
+            recorder.apply(&mut game, Action::Move(dir));
+        }
+        game.update(STEP_TIME);
+    }
+    s.replay = Some(recorder.finished(&game).expect("Game did not end.").clone());
+    s.output = Some(game);
+}
+#[when(expr = "the game is recorded while the cycle autopilot plays until it ends")]
+fn when_recorded_cycle(s: &mut State) {
+    let mut game = s.input.to_owned().unwrap();
+    let mut recorder = Recorder::new(&game);
+    let mut autopilot = Autopilot::for_mode(AutopilotMode::Cycle, &game).expect("No cycle for this board.");
+    while !game.is_over() && game.get_ticks() < 5000 {
+        if let Some(dir) = autopilot.next_move(&game) {
+            recorder.apply(&mut game, Action::Move(dir));
+        }
+        game.update(STEP_TIME);
+    }
+    s.replay = Some(recorder.finished(&game).expect("Game did not end.").clone());
+    s.output = Some(game);
+}
+fn opposite(dir: &Direction) -> Direction {
+    match dir {
+        Direction::Up => Direction::Down,
+        Direction::Down => Direction::Up,
+        Direction::Left => Direction::Right,
+        Direction::Right => Direction::Left,
+    }
+}
+#[then(expr = "the recorded game has points")]
+fn then_recorded_points(s: &mut State) {
+    assert!(s.replay.to_owned().unwrap().get_points() > 0, "Recorded game did not eat any apples.");
+}
+#[when(expr = "the snake starts moving up while recording")]
+fn when_recording_up(s: &mut State) {
+    let mut game = s.input.to_owned().unwrap();
+    let mut recorder = Recorder::new(&game);
+    recorder.apply(&mut game, Action::Move(Direction::Up));
+    game.update(STEP_TIME);
+    s.output = Some(game);
+    s.recorder = Some(recorder);
+}
+#[when(expr = "the game is paused and the left button is pressed while recording")]
+fn when_recording_paused(s: &mut State) {
+    let mut game = s.output.to_owned().unwrap();
+    let mut recorder = s.recorder.to_owned().unwrap();
+    recorder.apply(&mut game, Action::Pause);
+    recorder.apply(&mut game, Action::Move(Direction::Left));
+    s.output = Some(game);
+    s.recorder = Some(recorder);
+}
+#[when(expr = "the replay is saved to a file and loaded")]
+fn when_replay_saved(s: &mut State) {
+    let replay = s.replay.to_owned().unwrap();
+    let path = std::env::temp_dir().join(format!("snake_game_replay_{}", std::process::id())).join("replay.json");
+    replay.save(&path).unwrap();
+    s.loaded_replay = Some(Replay::load(&path).unwrap());
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+#[when(expr = "the recorded points are changed to {int}")]
+fn when_points_changed(s: &mut State, points: i32) {
+    let replay = s.replay.to_owned().unwrap();
+    let mut json: serde_json::Value = serde_json::to_value(&replay).unwrap();
+    json["points"] = points.into();
+    s.replay = Some(Replay::parse(&json.to_string()).unwrap());
+}
+#[when(expr = "the replay has its {word} set to {string} and is loaded")]
+fn when_replay_corrupted(s: &mut State, field: String, value: String) {
+    let replay = s.replay.to_owned().unwrap();
+    let mut json: serde_json::Value = serde_json::to_value(&replay).unwrap();
+    json[field] = serde_json::from_str(&value).unwrap();
+    s.replay_error = Replay::parse(&json.to_string()).err().map(|err| err.to_string());
+}
+#[when(expr = "we load the replay {string}")]
+fn when_load_replay(s: &mut State, replay: String) {
+    s.replay_error = Replay::parse(&replay).err().map(|err| err.to_string());
+}
+#[then(expr = "the replay plays back to the recorded points and ticks")]
+fn then_replay_verifies(s: &mut State) {
+    let replay = s.replay.to_owned().unwrap();
+    let recorded = s.output.to_owned().unwrap();
+    let played = replay.verify().unwrap();
+    assert_eq!(recorded.get_points(), played.get_points(), "Replay ended with different points.");
+    assert_eq!(recorded.get_ticks(), played.get_ticks(), "Replay ended after a different number of ticks.");
+    assert_eq!(recorded.get_state(), played.get_state(), "Replay ended in a different state.");
+    assert_eq!(recorded.get_snake().get_head_pos(), played.get_snake().get_head_pos(), "Replay ended with the snake somewhere else.");
+    assert_eq!(recorded.get_apple_loc(), played.get_apple_loc(), "Replay ended with the apple somewhere else.");
+}
+#[then(expr = "the loaded replay matches the recorded one")]
+fn then_replay_matches(s: &mut State) {
+    assert_eq!(s.replay, s.loaded_replay, "Loaded replay differs from the recorded one.");
+}
+#[then(expr = "the replay inputs start with {string}")]
+fn then_replay_inputs(s: &mut State, inputs: String) {
+    let replay = s.replay.to_owned().unwrap();
+    let json = serde_json::to_value(&replay).unwrap();
+    let stored = json["inputs"].as_str().unwrap();
+    assert!(stored.starts_with(&inputs), "Inputs were stored as {stored}.");
+}
+#[then(expr = "the replay has {int} inputs")]
+fn then_replay_input_count(s: &mut State, count: usize) {
+    let recorder = s.recorder.to_owned().unwrap();
+    assert_eq!(count, recorder.get_replay().unwrap().get_inputs().len(), "Unexpected number of recorded inputs.");
+}
+#[then(expr = "the replay fails to verify with {string}")]
+fn then_replay_fails(s: &mut State, error: String) {
+    let Err(replay_error) = s.replay.to_owned().unwrap().verify() else {
+        panic!("Replay verified unexpectedly.");
+    };
+    let replay_error = replay_error.to_string();
+    assert!(replay_error.contains(&error), "Unexpected replay error: {replay_error}");
+}
+#[then(expr = "the replay is rejected with {string}")]
+fn then_replay_rejected(s: &mut State, error: String) {
+    let replay_error = s.replay_error.to_owned().expect("Replay was accepted unexpectedly.");
+    assert!(replay_error.contains(&error), "Unexpected replay error: {replay_error}");
+}
+
+//Autopilot
+#[given(expr = "we have a snake game with the apple at {int},{int}")]
+fn given_snake_with_apple(s: &mut State, x: i32, y: i32) {
+    let (w, h) = (20, 20);
+    s.input = Some(Game::new_constructed(
+        (w, h),
+        Snake::init_snake(3, Direction::Right, (w, h)),
+        GameState::Waiting,
+        0.0,
+        (x, y),
+        0,
+        0
+    ));
+}
+#[when(expr = "the autopilot plays for {int} ticks")]
+fn when_autopilot_plays(s: &mut State, ticks: u64) {
+    let mut output = s.input.to_owned().unwrap();
+    Autopilot::new().play(&mut output, ticks);
+    s.output = Some(output);
+}
+#[when(expr = "the cycle autopilot plays for {int} ticks")]
+fn when_cycle_autopilot_plays(s: &mut State, ticks: u64) {
+    let mut output = s.input.to_owned().unwrap();
+    let mut autopilot = Autopilot::for_mode(AutopilotMode::Cycle, &output).expect("No cycle for this board.");
+    autopilot.play(&mut output, ticks);
+    s.output = Some(output);
+}
+#[then(expr = "for every seed from {int} to {int} the {word} autopilot starts a {int} by {int} game with a snake of length {int}")]
+fn then_autopilot_starts(_s: &mut State, from: u64, to: u64, mode: String, w: i32, h: i32, length: i32) {
+    let mode = AutopilotMode::from_str(&mode).unwrap();
+    for seed in from..=to {
+        let mut game = Game::with_length(w, h, length, seed);
+        let mut autopilot = Autopilot::for_mode(mode, &game).expect("No cycle for this board.");
+        //Like the window and the terminal: ask for a move every frame, then let the clock run
+        for _ in 0..50 {
+            if let Some(dir) = autopilot.next_move(&game) {
+                game.update_move_dir(dir);
+            }
+            game.update(STEP_TIME / 2.0);
+        }
+        assert!(game.get_ticks() > 0, "The autopilot never started the game with seed {seed}.");
+    }
+}
+#[then(expr = "the cycle visits every cell once")]
+fn then_cycle_visits_every_cell(s: &mut State) {
+    let input = s.input.to_owned().unwrap();
+    let cycle = HamiltonCycle::new(&GameView::new(&input)).expect("No cycle for this board.");
+    let (w, h) = input.get_game_size();
+    let mut cells = cycle.get_cycle().to_vec();
+    assert_eq!(cells.len(), (w * h) as usize, "The cycle has the wrong length.");
+    //Each cell is one move from the next, through the edge if it wraps
+    for (i, &(x, y)) in cells.iter().enumerate() {
+        let (nx, ny) = cells[(i + 1) % cells.len()];
+        let (dx, dy) = ((x - nx).abs(), (y - ny).abs());
+        let (dx, dy) = match input.get_edge_policy() {
+            EdgePolicy::Wrap => (dx.min(w - dx), dy.min(h - dy)),
+            EdgePolicy::Solid => (dx, dy),
+        };
+        assert_eq!(dx + dy, 1, "The cycle jumps from {:?} to {:?}.", (x, y), (nx, ny));
+    }
+    cells.sort();
+    cells.dedup();
+    assert_eq!(cells.len(), (w * h) as usize, "The cycle visits a cell twice.");
+}
+#[then(expr = "there is no cycle for the autopilot")]
+fn then_no_cycle(s: &mut State) {
+    assert!(HamiltonCycle::new(&GameView::new(&s.input.to_owned().unwrap())).is_none(), "Found a cycle.");
+}
+#[then(expr = "the autopilot steers {dir}")]
+fn then_autopilot_steers(s: &mut State, dir: CuDirection) {
+    assert_eq!(dir, choose_direction(&GameView::new(&latest_game(s))), "Autopilot picked the wrong direction.");
+}
+#[then(expr = "it has at least {int} points")]
+fn then_at_least_points(s: &mut State, points: i32) {
+    let output = s.output.to_owned().unwrap();
+    assert!(output.get_points() >= points, "Only scored {} points.", output.get_points());
+}
+
+//Rendering
+//Remembers what a game draws instead of drawing it
+#[derive(Debug, Default, Clone)]
+struct DrawLog {
+    cells: Vec<(Cell, i32, i32)>,
+    texts: Vec<String>,
+}
+impl Renderer for DrawLog {
+    fn clear(&mut self, _colour: Color) {
+        *self = DrawLog::default();
+    }
+    fn draw_cell(&mut self, cell: Cell, _colour: Color, x: i32, y: i32) {
+        self.cells.push((cell, x, y));
+    }
+    fn draw_text(&mut self, _colour: Color, _size: u32, _pos: (f64, f64), text: &str) {
+        self.texts.push(text.to_string());
+    }
+}
+#[when(expr = "the game is drawn")]
+fn when_game_drawn(s: &mut State) {
+    let game = latest_game(s);
+    game.draw(&mut s.drawn, &Colours::default(), "Press P to resume");
+}
+#[then(expr = "{int} {word} cells are drawn")]
+fn then_cells_drawn(s: &mut State, count: usize, kind: String) {
+    let cell = match kind.as_str() {
+        "snake" => Cell::Snake,
+        "apple" => Cell::Apple,
+        "obstacle" => Cell::Obstacle,
+        "wall" => Cell::Wall,
+        "fill" => Cell::Fill,
+        invalid => panic!("Invalid cell: {invalid}"),
+    };
+    let drawn = s.drawn.cells.iter().filter(|(drawn, _, _)| *drawn == cell).count();
+    assert_eq!(count, drawn, "Wrong number of {kind} cells drawn.");
+}
+#[then(expr = "a {word} cell is drawn at {int},{int}")]
+fn then_cell_drawn_at(s: &mut State, kind: String, x: i32, y: i32) {
+    let cells: Vec<_> = s.drawn.cells.iter().filter(|(_, cx, cy)| (*cx, *cy) == (x, y)).collect();
+    assert!(cells.iter().any(|(cell, _, _)| format!("{cell:?}").eq_ignore_ascii_case(&kind)), "Drawn at {x},{y}: {cells:?}");
+}
+#[then(expr = "the text {string} is drawn")]
+fn then_text_drawn(s: &mut State, text: String) {
+    assert!(s.drawn.texts.contains(&text), "Drawn texts: {:?}", s.drawn.texts);
+}
+
+//Snapshots
+//Golden images live in tests/golden, run with UPDATE_GOLDEN=1 to rewrite them after a deliberate change
+fn snapshot(s: &State) -> Framebuffer {
+    Framebuffer::snapshot(&latest_game(s), &Colours::default(), "Press P to resume")
+}
+#[then(expr = "the snapshot matches {string}")]
+fn then_snapshot_matches(s: &mut State, name: String) {
+    let actual = snapshot(s);
+    let golden = Path::new("tests/golden").join(&name);
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        actual.save_png(&golden).unwrap();
+        return;
+    }
+    let expected = Framebuffer::load_png(&golden)
+        .unwrap_or_else(|err| panic!("Could not read {}: {err}, run with UPDATE_GOLDEN=1 to create it.", golden.display()));
+    let differences = actual.count_differences(&expected, 2);
+    if differences > 0 {
+        let failed = Path::new("target/golden-failures").join(&name);
+        actual.save_png(&failed).unwrap();
+        panic!("{differences} pixels differ from {}, the snapshot was saved to {}.", golden.display(), failed.display());
+    }
+}
+#[then(expr = "the snapshot is {int} by {int} pixels")]
+fn then_snapshot_size(s: &mut State, width: u32, height: u32) {
+    assert_eq!((width, height), snapshot(s).get_size(), "Snapshot has the wrong size.");
+}
+#[then(expr = "the snapshot pixel at {int},{int} is {int},{int},{int}")]
+fn then_snapshot_pixel(s: &mut State, x: u32, y: u32, r: u8, g: u8, b: u8) {
+    assert_eq!([r, g, b, 255], snapshot(s).get_pixel(x, y), "Pixel {x},{y} has the wrong colour.");
+}
+#[then(expr = "the snapshot is the same after a PNG round trip")]
+fn then_snapshot_round_trip(s: &mut State) {
+    let image = snapshot(s);
+    let loaded = Framebuffer::from_png(&image.to_png().unwrap()).unwrap();
+    assert_eq!(image, loaded, "Snapshot changed when written and read as PNG.");
+}
+
+//Export
+#[when(expr = "the game is exported as a GIF before it starts")]
+fn when_export_unplayed(s: &mut State) {
+    let replay = Replay::new(s.input.as_ref().unwrap()).unwrap();
+    s.export_error = write_gif(&replay, &Colours::default(), Vec::new()).err().map(|err| err.to_string());
+}
+#[then(expr = "the export fails with {string}")]
+fn then_export_fails(s: &mut State, error: String) {
+    let export_error = s.export_error.to_owned().expect("Export succeeded unexpectedly.");
+    assert!(export_error.contains(&error), "Unexpected export error: {export_error}");
+}
+#[when(expr = "the replay is exported as a GIF")]
+fn when_export_gif(s: &mut State) {
+    let mut gif = Vec::new();
+    write_gif(s.replay.as_ref().unwrap(), &Colours::default(), &mut gif).unwrap();
+    s.gif = Some(gif);
+}
+//Size and delay in hundredths of a second of every frame
+fn gif_frames(s: &State) -> Vec<(u16, u16, u16)> {
+    let mut options = gif::DecodeOptions::new();
+    options.set_color_output(gif::ColorOutput::RGBA);
+    let mut decoder = options.read_info(s.gif.as_deref().unwrap()).unwrap();
+    let mut frames = Vec::new();
+    while let Some(frame) = decoder.read_next_frame().unwrap() {
+        frames.push((frame.width, frame.height, frame.delay));
+    }
+    frames
+}
+#[then(expr = "the GIF has a frame for every tick and one for the end")]
+fn then_gif_frame_count(s: &mut State) {
+    let ticks = s.replay.as_ref().unwrap().get_ticks() as usize;
+    assert_eq!(ticks + 1, gif_frames(s).len(), "GIF has the wrong number of frames.");
+}
+#[then(expr = "every GIF frame is {int} by {int} pixels")]
+fn then_gif_frame_size(s: &mut State, width: u16, height: u16) {
+    for (w, h, _) in gif_frames(s) {
+        assert_eq!((width, height), (w, h), "GIF frame has the wrong size.");
+    }
+}
+#[then(expr = "GIF frame {int} lasts {int} hundredths of a second")]
+fn then_gif_delay(s: &mut State, frame: usize, delay: u16) {
+    assert_eq!(delay, gif_frames(s)[frame - 1].2, "Frame {frame} has the wrong delay.");
+}
+#[then(expr = "the last GIF frame lasts {int} hundredths of a second")]
+fn then_gif_last_delay(s: &mut State, delay: u16) {
+    assert_eq!(delay, gif_frames(s).last().unwrap().2, "Last frame has the wrong delay.");
+}
+
+//Terminal
+fn terminal_key(name: &str) -> KeyCode {
+    match name {
+        "Up" => KeyCode::Up,
+        "Down" => KeyCode::Down,
+        "Left" => KeyCode::Left,
+        "Right" => KeyCode::Right,
+        "Enter" => KeyCode::Enter,
+        "Esc" => KeyCode::Esc,
+        "Space" => KeyCode::Char(' '),
+        "Home" => KeyCode::Home,
+        name if name.starts_with('F') && name.len() > 1 => KeyCode::F(name[1..].parse().unwrap()),
+        name => KeyCode::Char(name.chars().next().unwrap()),
+    }
+}
+#[then(expr = "the terminal key {word} means {action} while playing")]
+fn then_terminal_key_playing(s: &mut State, name: String, action: CuAction) {
+    let settings = s.settings.to_owned().unwrap_or_default();
+    let key = key_from_terminal(terminal_key(&name));
+    let found = key.and_then(|key| settings.keys.action(key, false));
+    assert_eq!(Option::<Action>::from(action), found, "Terminal key {name} is bound to the wrong action.");
+}
+#[then(expr = "the terminal key {word} means {action} when the game is over")]
+fn then_terminal_key_over(s: &mut State, name: String, action: CuAction) {
+    let settings = s.settings.to_owned().unwrap_or_default();
+    let key = key_from_terminal(terminal_key(&name));
+    let found = key.and_then(|key| settings.keys.action(key, true));
+    assert_eq!(Option::<Action>::from(action), found, "Terminal key {name} is bound to the wrong action.");
+}
+#[then(expr = "the terminal key {word} is not a game key")]
+fn then_terminal_key_ignored(_s: &mut State, name: String) {
+    assert_eq!(None, key_from_terminal(terminal_key(&name)), "Terminal key {name} was translated.");
+}
+#[when(expr = "the game is paused")]
+fn when_game_paused(s: &mut State) {
+    let mut output = latest_game(s);
+    output.pause();
+    s.output = Some(output);
+}
+#[then(expr = "terminal line {int} reads {string}")]
+fn then_terminal_line(s: &mut State, line: usize, text: String) {
+    let settings = s.settings.to_owned().unwrap_or_default();
+    let lines = frame_lines(&latest_game(s), &settings);
+    assert_eq!(text, lines[line - 1], "Terminal shows:\n{}", lines.join("\n"));
+}
+
+//Tournaments
+//A plug-in bot that never turns
+struct Stubborn(Direction);
+impl Agent for Stubborn {
+    fn name(&self) -> &str {
+        "stubborn"
+    }
+    fn choose(&mut self, _view: &GameView) -> Direction {
+        self.0.clone()
+    }
+}
+fn tournament_report(s: &State, bot: &str) -> Report {
+    let tournament = s.tournament.to_owned().expect("No tournament.");
+    let mode = AutopilotMode::from_str(bot).unwrap();
+    tournament.play(tournament.bot(mode).expect("The bot cannot play this board."))
+}
+fn last_report(s: &State) -> Report {
+    s.reports.last().cloned().expect("No tournament was played.")
+}
+#[given(expr = "a tournament of {int} games on a {int} by {int} board with {edges} edges")]
+fn given_tournament(s: &mut State, games: u32, w: i32, h: i32, edges: CuEdges) {
+    let mut template = Game::new(w, h, 0);
+    template.set_edge_policy(edges.into());
+    s.tournament = Some(Tournament::new(template, games, 1, 20_000));
+}
+#[when(expr = "the tournament is started with {string}")]
+fn when_tournament_started(s: &mut State, args: String) {
+    let args = std::iter::once("tournament").chain(args.split_whitespace());
+    let tournament = TournamentOptions::try_parse_from(args)
+        .map_err(|err| err.to_string())
+        .and_then(|options| options.build().map_err(|err| err.to_string()));
+    match tournament {
+        Ok(tournament) => s.tournament = Some(tournament),
+        Err(err) => s.options_error = Some(err),
+    }
+}
+#[when(expr = "the {word} bot plays the tournament")]
+fn when_bot_plays_tournament(s: &mut State, bot: String) {
+    let report = tournament_report(s, &bot);
+    s.reports.push(report);
+}
+#[when(expr = "a bot that always goes {dir} plays the tournament")]
+fn when_stubborn_plays_tournament(s: &mut State, dir: CuDirection) {
+    let tournament = s.tournament.to_owned().expect("No tournament.");
+    s.reports.push(tournament.play(Box::new(Stubborn(dir.into()))));
+}
+#[then(expr = "the {word} bot cannot play the tournament")]
+fn then_bot_cannot_play(s: &mut State, bot: String) {
+    let tournament = s.tournament.to_owned().expect("No tournament.");
+    let mode = AutopilotMode::from_str(&bot).unwrap();
+    assert!(tournament.bot(mode).is_none(), "The {bot} bot was accepted.");
+}
+#[then(expr = "the report covers {int} games")]
+fn then_report_games(s: &mut State, games: usize) {
+    assert_eq!(games, last_report(s).results.len(), "Wrong number of games.");
+}
+#[then(expr = "{int} games ended with {string}")]
+fn then_games_ended(s: &mut State, count: usize, ending: String) {
+    let report = last_report(s);
+    let ending = [Ending::Won, Ending::HitItself, Ending::HitWall, Ending::HitObstacle, Ending::OutOfTime]
+        .into_iter()
+        .find(|known| known.to_string() == ending)
+        .expect("Unknown ending.");
+    assert_eq!(count, report.count(ending), "Report:\n{report}");
+}
+#[then(expr = "the mean score is at least {int}")]
+fn then_mean_score(s: &mut State, points: i32) {
+    let report = last_report(s);
+    assert!(report.mean_points() >= points as f64, "Report:\n{report}");
+}
+#[then(expr = "the median score is {float}")]
+fn then_median_score(s: &mut State, median: f64) {
+    let report = last_report(s);
+    assert_eq!(median, report.median_points(), "Report:\n{report}");
+}
+#[then(expr = "every game lasted {int} ticks")]
+fn then_games_lasted(s: &mut State, ticks: u64) {
+    let report = last_report(s);
+    assert!(report.results.iter().all(|result| result.ticks == ticks), "Report:\n{report}");
+}
+#[then(expr = "the last two reports are the same")]
+fn then_reports_same(s: &mut State) {
+    let [.., first, second] = s.reports.as_slice() else {
+        panic!("Fewer than two reports.");
+    };
+    assert_eq!(first, second, "The reports differ.");
+}
+#[then(expr = "the report says {string}")]
+fn then_report_says(s: &mut State, text: String) {
+    let report = last_report(s).to_string();
+    assert!(report.contains(&text), "Report:\n{report}");
+}
+
+//External bots
+//Every bot logs into its own file, scenarios may run at the same time
+static BOT_LOGS: AtomicUsize = AtomicUsize::new(0);
+//Scenarios that are not about timeouts must not fail on a busy machine
+const GENEROUS_TIMEOUT: Duration = Duration::from_secs(5);
+fn spawn_bot(command: &str, timeout: Duration) -> (ExternalBot, std::path::PathBuf) {
+    let dir = Path::new("target/bot-logs");
+    std::fs::create_dir_all(dir).unwrap();
+    let log = dir.join(format!("{}.log", BOT_LOGS.fetch_add(1, Ordering::Relaxed)));
+    let bot = ExternalBot::spawn(command, timeout, Some(&log)).expect("Could not start the bot.");
+    (bot, log)
+}
+fn external_bot_plays(s: &mut State, command: &str, ticks: u64, timeout: Duration) {
+    let (bot, log) = spawn_bot(command, timeout);
+    let mut output = s.input.to_owned().unwrap();
+    //Dropping the autopilot stops the bot
+    Autopilot::with_agent(Box::new(bot)).play(&mut output, ticks);
+    s.output = Some(output);
+    s.bot_log = Some(std::fs::read_to_string(log).unwrap());
+}
+#[when(expr = "the bot {string} plays for {int} ticks")]
+fn when_external_plays(s: &mut State, command: String, ticks: u64) {
+    external_bot_plays(s, &command, ticks, GENEROUS_TIMEOUT);
+}
+#[when(expr = "the bot {string} plays for {int} ticks with a timeout of {int} ms")]
+fn when_external_plays_timeout(s: &mut State, command: String, ticks: u64, timeout: u64) {
+    external_bot_plays(s, &command, ticks, Duration::from_millis(timeout));
+}
+#[when(expr = "the external bot {string} plays the tournament")]
+fn when_external_plays_tournament(s: &mut State, command: String) {
+    let tournament = s.tournament.to_owned().expect("No tournament.");
+    let (bot, _) = spawn_bot(&command, GENEROUS_TIMEOUT);
+    s.reports.push(tournament.play(Box::new(bot)));
+}
+#[then(expr = "the bot log contains {string}")]
+fn then_bot_log_contains(s: &mut State, text: String) {
+    let log = s.bot_log.to_owned().expect("No bot has played.");
+    assert!(log.contains(&text), "Bot log:\n{log}");
+}
+#[then(expr = "the bot log has {int} lines starting with {string}")]
+fn then_bot_log_lines(s: &mut State, count: usize, start: String) {
+    let log = s.bot_log.to_owned().expect("No bot has played.");
+    assert_eq!(count, log.lines().filter(|line| line.starts_with(&start)).count(), "Bot log:\n{log}");
+}
@@ -0,0 +1,9 @@
+#!/bin/sh
+# Too slow for the first move, then answers up right away. With a timeout of 1 s the late
+# answer comes half way through the second move, leaving half a second either side.
+read -r line
+sleep 1.5
+echo '{"move":"down"}'
+while read -r line; do
+    echo '{"move":"up"}'
+done
@@ -0,0 +1,5 @@
+#!/bin/sh
+# Answers every line with its first argument, e.g. {"move":"up"}
+while read -r line; do
+    echo "$1"
+done